@@ -0,0 +1,797 @@
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::io::{self, BufRead, Write};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+pub mod storage;
+
+// •Using a hash map and vectors, create a text interface to allow a user to add
+//employee names to a department in a company.
+//For example, “Add Sally to Engineering” or
+//  “Add Amir to Sales.” Then let the user retrieve a list of all people in a
+//department or all people in the company by department, sorted alphabetically.
+// The standard library API documentation describes methods that vectors, strings, and hash maps have that will be helpful for these exercises!
+
+const DEFAULT_TITLE: &str = "Staff";
+
+#[derive(Debug, PartialEq)]
+pub enum DirectoryError {
+    UnknownEmployee(u32),
+    DuplicateEmployee(u32),
+    MissingField { id: u32, field: &'static str },
+    ReportingCycle(Vec<u32>),
+    InvalidDate(String),
+    InvalidCommand(String),
+    /// Every id up to `u32::MAX` has been handed out.
+    IdsExhausted,
+    /// A stored id of `u32::MAX`, which [`Directory::hire`] never hands out.
+    IdOutOfRange(u32),
+}
+
+impl fmt::Display for DirectoryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DirectoryError::UnknownEmployee(id) => write!(f, "No employee with id {}", id),
+            DirectoryError::DuplicateEmployee(id) => write!(f, "Employee id {} is used more than once", id),
+            DirectoryError::MissingField { id, field } => write!(f, "Employee {} has an empty {}", id, field),
+            DirectoryError::ReportingCycle(path) => {
+                let path: Vec<String> = path.iter().map(|id| id.to_string()).collect();
+                write!(f, "Reporting cycle detected: {}", path.join(" -> "))
+            }
+            DirectoryError::InvalidDate(s) => write!(f, "Invalid date '{}', expected YYYY-MM-DD", s),
+            DirectoryError::InvalidCommand(s) => write!(f, "Unrecognised command '{}'", s),
+            DirectoryError::IdsExhausted => write!(f, "No employee ids left"),
+            DirectoryError::IdOutOfRange(id) => write!(f, "Employee id {} is out of range", id),
+        }
+    }
+}
+
+impl Error for DirectoryError {}
+
+/// A calendar date used for employee start dates, written as `YYYY-MM-DD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Date {
+    year: i32,
+    month: u32,
+    day: u32,
+}
+
+impl Date {
+    pub fn new(year: i32, month: u32, day: u32) -> Option<Date> {
+        if !(1..=12).contains(&month) || day == 0 || day > days_in_month(year, month) {
+            return None;
+        }
+
+        Some(Date { year, month, day })
+    }
+
+    /// Today's date in UTC, based on the system clock.
+    pub fn today() -> Date {
+        let secs = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+
+        Date::from_days_since_epoch((secs / 86_400) as i64)
+    }
+
+    pub fn year(&self) -> i32 {
+        self.year
+    }
+
+    pub fn month(&self) -> u32 {
+        self.month
+    }
+
+    pub fn day(&self) -> u32 {
+        self.day
+    }
+
+    // Howard Hinnant's `civil_from_days` algorithm.
+    fn from_days_since_epoch(days: i64) -> Date {
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1_460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+
+        Date { year, month, day }
+    }
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+impl FromStr for Date {
+    type Err = DirectoryError;
+
+    fn from_str(s: &str) -> Result<Date, DirectoryError> {
+        let invalid = || DirectoryError::InvalidDate(s.to_owned());
+        let mut parts = s.trim().splitn(3, '-');
+
+        let year = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let month = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let day = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+
+        Date::new(year, month, day).ok_or_else(invalid)
+    }
+}
+
+impl fmt::Display for Date {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:04}-{:02}-{:02}", self.year, self.month, self.day)
+    }
+}
+
+impl TryFrom<String> for Date {
+    type Error = DirectoryError;
+
+    fn try_from(s: String) -> Result<Date, DirectoryError> {
+        s.parse()
+    }
+}
+
+impl From<Date> for String {
+    fn from(date: Date) -> String {
+        date.to_string()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Employee {
+    pub id: u32,
+    pub full_name: String,
+    pub title: String,
+    pub department: String,
+    pub manager: Option<u32>,
+    pub start_date: Date,
+}
+
+impl fmt::Display for Employee {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "#{} {} ({}, {})", self.id, self.full_name, self.title, self.department)
+    }
+}
+
+/// Every employee in the company, keyed by id.
+///
+/// Manager links are validated on every change so the directory never holds a
+/// reporting cycle.
+#[derive(Debug)]
+pub struct Directory {
+    employees: BTreeMap<u32, Employee>,
+    next_id: u32,
+}
+
+impl Directory {
+    pub fn new() -> Directory {
+        Directory { employees: BTreeMap::new(), next_id: 1 }
+    }
+
+    /// Adds a new employee without a manager and returns their id.
+    ///
+    /// `u32::MAX` is never handed out, so the next id always fits in a `u32`.
+    pub fn hire(&mut self, full_name: &str, title: &str, department: &str, start_date: Date) -> Result<u32, DirectoryError> {
+        let id = self.next_id;
+        self.next_id = id.checked_add(1).ok_or(DirectoryError::IdsExhausted)?;
+
+        let employee = Employee {
+            id,
+            full_name: full_name.to_owned(),
+            title: title.to_owned(),
+            department: department.to_owned(),
+            manager: None,
+            start_date,
+        };
+        self.employees.insert(id, employee);
+
+        Ok(id)
+    }
+
+    /// Builds a directory from existing records, e.g. ones read from disk.
+    ///
+    /// Rejects duplicate or out-of-range ids, blank names or departments,
+    /// managers that do not exist and reporting cycles.
+    pub fn from_employees(employees: Vec<Employee>) -> Result<Directory, DirectoryError> {
+        let mut directory = Directory::new();
+
+        for employee in employees {
+            if employee.full_name.trim().is_empty() {
+                return Err(DirectoryError::MissingField { id: employee.id, field: "full name" });
+            }
+            if employee.department.trim().is_empty() {
+                return Err(DirectoryError::MissingField { id: employee.id, field: "department" });
+            }
+
+            let id = employee.id;
+            if directory.employees.insert(id, employee).is_some() {
+                return Err(DirectoryError::DuplicateEmployee(id));
+            }
+            let next_id = id.checked_add(1).ok_or(DirectoryError::IdOutOfRange(id))?;
+            directory.next_id = directory.next_id.max(next_id);
+        }
+
+        for employee in directory.employees.values() {
+            if let Some(manager) = employee.manager {
+                if !directory.employees.contains_key(&manager) {
+                    return Err(DirectoryError::UnknownEmployee(manager));
+                }
+            }
+        }
+
+        if let Some(cycle) = directory.find_cycle() {
+            return Err(DirectoryError::ReportingCycle(cycle));
+        }
+
+        Ok(directory)
+    }
+
+    pub fn get(&self, id: u32) -> Option<&Employee> {
+        self.employees.get(&id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.employees.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.employees.is_empty()
+    }
+
+    /// All employees in id order.
+    pub fn employees(&self) -> impl Iterator<Item = &Employee> {
+        self.employees.values()
+    }
+
+    /// Makes `manager` the manager of `id`, or clears it with `None`.
+    ///
+    /// Fails without changing anything if either id is unknown or the new link
+    /// would close a reporting cycle.
+    pub fn set_manager(&mut self, id: u32, manager: Option<u32>) -> Result<(), DirectoryError> {
+        if !self.employees.contains_key(&id) {
+            return Err(DirectoryError::UnknownEmployee(id));
+        }
+
+        if let Some(manager_id) = manager {
+            if !self.employees.contains_key(&manager_id) {
+                return Err(DirectoryError::UnknownEmployee(manager_id));
+            }
+
+            let mut path = vec![id];
+            let mut current = Some(manager_id);
+            while let Some(next) = current {
+                path.push(next);
+                if next == id {
+                    return Err(DirectoryError::ReportingCycle(path));
+                }
+                current = self.employees.get(&next).and_then(|e| e.manager);
+            }
+        }
+
+        if let Some(employee) = self.employees.get_mut(&id) {
+            employee.manager = manager;
+        }
+
+        Ok(())
+    }
+
+    /// Employees in `department`, sorted alphabetically by name.
+    pub fn department(&self, department: &str) -> Vec<&Employee> {
+        let mut members: Vec<&Employee> = self
+            .employees
+            .values()
+            .filter(|e| e.department == department)
+            .collect();
+        members.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+
+        members
+    }
+
+    /// Every department with its members sorted alphabetically by name.
+    pub fn by_department(&self) -> BTreeMap<&str, Vec<&Employee>> {
+        let mut departments: BTreeMap<&str, Vec<&Employee>> = BTreeMap::new();
+
+        for employee in self.employees.values() {
+            departments.entry(employee.department.as_str()).or_default().push(employee);
+        }
+
+        for members in departments.values_mut() {
+            members.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+        }
+
+        departments
+    }
+
+    /// People reporting directly to `id`, sorted by name.
+    pub fn direct_reports(&self, id: u32) -> Vec<&Employee> {
+        let mut reports: Vec<&Employee> = self
+            .employees
+            .values()
+            .filter(|e| e.manager == Some(id))
+            .collect();
+        reports.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+
+        reports
+    }
+
+    /// Everyone below `id` in the reporting tree, nearest first.
+    pub fn transitive_reports(&self, id: u32) -> Result<Vec<&Employee>, DirectoryError> {
+        if !self.employees.contains_key(&id) {
+            return Err(DirectoryError::UnknownEmployee(id));
+        }
+
+        let mut reports = Vec::new();
+        let mut seen = HashSet::from([id]);
+        let mut queue = VecDeque::from([id]);
+
+        while let Some(current) = queue.pop_front() {
+            for report in self.direct_reports(current) {
+                if seen.insert(report.id) {
+                    reports.push(report);
+                    queue.push_back(report.id);
+                }
+            }
+        }
+
+        Ok(reports)
+    }
+
+    /// Returns the ids along the first reporting cycle found, starting and
+    /// ending with the same employee.
+    pub fn find_cycle(&self) -> Option<Vec<u32>> {
+        let mut cleared: HashSet<u32> = HashSet::new();
+
+        for &start in self.employees.keys() {
+            let mut path: Vec<u32> = Vec::new();
+            let mut current = Some(start);
+
+            while let Some(id) = current {
+                if cleared.contains(&id) {
+                    break;
+                }
+                if let Some(pos) = path.iter().position(|&p| p == id) {
+                    let mut cycle = path[pos..].to_vec();
+                    cycle.push(id);
+                    return Some(cycle);
+                }
+                path.push(id);
+                current = self.employees.get(&id).and_then(|e| e.manager);
+            }
+
+            cleared.extend(path);
+        }
+
+        None
+    }
+
+    /// Renders the manager → reports tree, one employee per line.
+    pub fn org_chart(&self) -> String {
+        let mut chart = String::new();
+        let mut roots: Vec<&Employee> = self
+            .employees
+            .values()
+            .filter(|e| e.manager.is_none_or(|m| !self.employees.contains_key(&m)))
+            .collect();
+        roots.sort_by(|a, b| a.full_name.cmp(&b.full_name));
+
+        for root in roots {
+            chart.push_str(&format!("{}\n", root));
+            self.write_reports(&mut chart, root.id, "");
+        }
+
+        chart
+    }
+
+    fn write_reports(&self, chart: &mut String, id: u32, prefix: &str) {
+        let reports = self.direct_reports(id);
+
+        for (index, report) in reports.iter().enumerate() {
+            let last = index == reports.len() - 1;
+            let (branch, indent) = if last { ("└── ", "    ") } else { ("├── ", "│   ") };
+
+            chart.push_str(&format!("{}{}{}\n", prefix, branch, report));
+            self.write_reports(chart, report.id, &format!("{}{}", prefix, indent));
+        }
+    }
+}
+
+// Ids start at 1, which a derived `Default` would not know.
+impl Default for Directory {
+    fn default() -> Directory {
+        Directory::new()
+    }
+}
+
+/// A single line of the text interface.
+///
+/// - `Add <name> to <department> [as <title>] [since <YYYY-MM-DD>]`
+/// - `Report <id> to <manager id>`
+/// - `List <department>` or `List all`
+/// - `Reports <id>`
+/// - `Chart`
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Add {
+        name: String,
+        department: String,
+        title: Option<String>,
+        start_date: Option<Date>,
+    },
+    Report { id: u32, manager: u32 },
+    List(String),
+    ListAll,
+    Reports(u32),
+    Chart,
+}
+
+impl FromStr for Command {
+    type Err = DirectoryError;
+
+    fn from_str(s: &str) -> Result<Command, DirectoryError> {
+        let invalid = || DirectoryError::InvalidCommand(s.trim().to_owned());
+        let parse_id = |word: &str| word.parse::<u32>().map_err(|_| invalid());
+        let words: Vec<&str> = s.split_whitespace().collect();
+
+        match words.as_slice() {
+            ["Add", rest @ ..] => {
+                let to = rest.iter().position(|&w| w == "to").ok_or_else(invalid)?;
+                let (name, rest) = (&rest[..to], &rest[to + 1..]);
+
+                let (rest, start_date) = match rest.iter().position(|&w| w == "since") {
+                    Some(pos) if pos + 2 == rest.len() => (&rest[..pos], Some(rest[pos + 1].parse()?)),
+                    Some(_) => return Err(invalid()),
+                    None => (rest, None),
+                };
+                let (department, title) = match rest.iter().position(|&w| w == "as") {
+                    Some(pos) => (&rest[..pos], Some(rest[pos + 1..].join(" "))),
+                    None => (rest, None),
+                };
+
+                if name.is_empty() || department.is_empty() || title.as_deref() == Some("") {
+                    return Err(invalid());
+                }
+
+                Ok(Command::Add {
+                    name: name.join(" "),
+                    department: department.join(" "),
+                    title,
+                    start_date,
+                })
+            }
+            ["Report", id, "to", manager] => Ok(Command::Report {
+                id: parse_id(id)?,
+                manager: parse_id(manager)?,
+            }),
+            ["List", "all"] => Ok(Command::ListAll),
+            ["List", department @ ..] if !department.is_empty() => Ok(Command::List(department.join(" "))),
+            ["Reports", id] => Ok(Command::Reports(parse_id(id)?)),
+            ["Chart"] => Ok(Command::Chart),
+            _ => Err(invalid()),
+        }
+    }
+}
+
+/// Applies one command to the directory and returns the lines it prints.
+///
+/// `today` is used as the start date when an `Add` command does not give one.
+pub fn execute(directory: &mut Directory, command: Command, today: Date) -> Result<Vec<String>, DirectoryError> {
+    let mut lines = Vec::new();
+
+    match command {
+        Command::Add { name, department, title, start_date } => {
+            let title = title.as_deref().unwrap_or(DEFAULT_TITLE);
+            let id = directory.hire(&name, title, &department, start_date.unwrap_or(today))?;
+            lines.push(format!("Added {} to {} with id {}", name, department, id));
+        }
+        Command::Report { id, manager } => {
+            directory.set_manager(id, Some(manager))?;
+            lines.push(format!("{} now reports to {}", id, manager));
+        }
+        Command::List(department) => {
+            lines.extend(directory.department(&department).iter().map(|e| e.to_string()));
+        }
+        Command::ListAll => {
+            for (department, members) in directory.by_department() {
+                lines.push(format!("{}:", department));
+                lines.extend(members.iter().map(|e| format!("  {}", e)));
+            }
+        }
+        Command::Reports(id) => {
+            lines.extend(directory.transitive_reports(id)?.iter().map(|e| e.to_string()));
+        }
+        Command::Chart => lines.extend(directory.org_chart().lines().map(String::from)),
+    }
+
+    Ok(lines)
+}
+
+/// Reads commands line by line from `input` until `exit` or end of input,
+/// writing results and errors to `output`.
+pub fn run<R: BufRead, W: Write>(directory: &mut Directory, input: R, mut output: W) -> io::Result<()> {
+    writeln!(output, "Add Employee to Department: ")?;
+
+    for line in input.lines() {
+        let line = line?;
+        let s = line.trim();
+
+        if s == "exit" {
+            break;
+        }
+
+        let result = s.parse().and_then(|command| execute(directory, command, Date::today()));
+
+        match result {
+            Ok(lines) => {
+                for line in lines {
+                    writeln!(output, "{}", line)?;
+                }
+            }
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+
+        writeln!(output, "Add Employee to Department: ")?;
+    }
+
+    Ok(())
+}
+
+pub fn add(directory: &mut Directory) {
+    run(directory, io::stdin().lock(), io::stdout()).unwrap();
+}
+
+pub fn print_dept_employee(directory: &Directory) {
+    let mut s = String::new();
+
+    println!("Get Department Members: ");
+    std::io::stdin().read_line(&mut s).unwrap();
+
+    let input = s.trim();
+
+    let members = directory.department(input);
+
+    if !members.is_empty() {
+        println!("Here is a list of employees in the {} department", input);
+        for employee in members {
+            println!("{}", employee.full_name);
+        }
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date() -> Date {
+        Date::new(2024, 1, 15).unwrap()
+    }
+
+    fn company() -> (Directory, u32, u32, u32, u32) {
+        let mut directory = Directory::new();
+        let ceo = directory.hire("Amir Khan", "CEO", "Executive", date()).unwrap();
+        let cto = directory.hire("Sally Green", "CTO", "Engineering", date()).unwrap();
+        let dev = directory.hire("Bola Ade", "Engineer", "Engineering", date()).unwrap();
+        let intern = directory.hire("Ian Wu", "Intern", "Engineering", date()).unwrap();
+
+        directory.set_manager(cto, Some(ceo)).unwrap();
+        directory.set_manager(dev, Some(cto)).unwrap();
+        directory.set_manager(intern, Some(dev)).unwrap();
+
+        (directory, ceo, cto, dev, intern)
+    }
+
+    #[test]
+    fn should_parse_and_print_dates() {
+        let d: Date = "2024-02-29".parse().unwrap();
+
+        assert_eq!(d, Date::new(2024, 2, 29).unwrap());
+        assert_eq!(d.to_string(), "2024-02-29");
+        assert!("2023-02-29".parse::<Date>().is_err());
+        assert!("yesterday".parse::<Date>().is_err());
+        assert_eq!(Date::from_days_since_epoch(0), Date::new(1970, 1, 1).unwrap());
+        assert_eq!(Date::from_days_since_epoch(19_737), Date::new(2024, 1, 15).unwrap());
+    }
+
+    #[test]
+    fn should_list_transitive_reports() {
+        let (directory, ceo, cto, dev, intern) = company();
+
+        let ids: Vec<u32> = directory.transitive_reports(ceo).unwrap().iter().map(|e| e.id).collect();
+
+        assert_eq!(ids, vec![cto, dev, intern]);
+        assert!(directory.transitive_reports(intern).unwrap().is_empty());
+        assert_eq!(directory.transitive_reports(99), Err(DirectoryError::UnknownEmployee(99)));
+    }
+
+    #[test]
+    fn should_reject_reporting_cycle() {
+        let (mut directory, ceo, cto, dev, _) = company();
+
+        let result = directory.set_manager(ceo, Some(dev));
+
+        assert_eq!(result, Err(DirectoryError::ReportingCycle(vec![ceo, dev, cto, ceo])));
+        assert_eq!(directory.get(ceo).unwrap().manager, None);
+        assert_eq!(directory.find_cycle(), None);
+    }
+
+    #[test]
+    fn should_find_cycle_in_corrupted_links() {
+        let (mut directory, ceo, cto, _, _) = company();
+
+        directory.employees.get_mut(&ceo).unwrap().manager = Some(cto);
+
+        assert_eq!(directory.find_cycle(), Some(vec![ceo, cto, ceo]));
+    }
+
+    #[test]
+    fn should_render_org_chart() {
+        let (mut directory, ceo, ..) = company();
+        let cfo = directory.hire("Chidi Obi", "CFO", "Finance", date()).unwrap();
+        directory.set_manager(cfo, Some(ceo)).unwrap();
+
+        let expected = "\
+#1 Amir Khan (CEO, Executive)
+├── #5 Chidi Obi (CFO, Finance)
+└── #2 Sally Green (CTO, Engineering)
+    └── #3 Bola Ade (Engineer, Engineering)
+        └── #4 Ian Wu (Intern, Engineering)
+";
+        assert_eq!(directory.org_chart(), expected);
+    }
+
+    #[test]
+    fn should_group_departments_alphabetically() {
+        let (directory, ..) = company();
+
+        let names: Vec<&str> = directory.department("Engineering").iter().map(|e| e.full_name.as_str()).collect();
+
+        assert_eq!(names, vec!["Bola Ade", "Ian Wu", "Sally Green"]);
+        assert_eq!(directory.by_department().keys().copied().collect::<Vec<_>>(), vec!["Engineering", "Executive"]);
+    }
+
+    #[test]
+    fn should_start_default_directories_at_id_one() {
+        let mut directory = Directory::default();
+
+        assert_eq!(directory.hire("First", "Engineer", "Engineering", date()), Ok(1));
+    }
+
+    #[test]
+    fn should_refuse_to_hire_once_ids_run_out() {
+        let mut directory = Directory::new();
+        directory.next_id = u32::MAX - 1;
+
+        assert_eq!(directory.hire("Last One", "Engineer", "Engineering", date()), Ok(u32::MAX - 1));
+        assert_eq!(directory.hire("Too Late", "Engineer", "Engineering", date()), Err(DirectoryError::IdsExhausted));
+        assert_eq!(directory.len(), 1);
+    }
+
+    #[test]
+    fn should_build_directory_from_valid_records() {
+        let (directory, ..) = company();
+        let records: Vec<Employee> = directory.employees().cloned().collect();
+
+        let mut loaded = Directory::from_employees(records).unwrap();
+
+        assert_eq!(loaded.len(), 4);
+        assert_eq!(loaded.org_chart(), directory.org_chart());
+        assert_eq!(loaded.hire("New Hire", "Engineer", "Engineering", date()), Ok(5));
+    }
+
+    #[test]
+    fn should_reject_invalid_records() {
+        let (directory, ceo, cto, ..) = company();
+        let records: Vec<Employee> = directory.employees().cloned().collect();
+
+        let mut duplicated = records.clone();
+        duplicated.push(records[0].clone());
+        assert_eq!(Directory::from_employees(duplicated).unwrap_err(), DirectoryError::DuplicateEmployee(ceo));
+
+        let mut orphaned = records.clone();
+        orphaned[0].manager = Some(42);
+        assert_eq!(Directory::from_employees(orphaned).unwrap_err(), DirectoryError::UnknownEmployee(42));
+
+        let mut cyclic = records.clone();
+        cyclic[0].manager = Some(cto);
+        assert_eq!(
+            Directory::from_employees(cyclic).unwrap_err(),
+            DirectoryError::ReportingCycle(vec![ceo, cto, ceo])
+        );
+
+        let mut unnamed = records;
+        unnamed[1].full_name = "  ".into();
+        assert_eq!(
+            Directory::from_employees(unnamed).unwrap_err(),
+            DirectoryError::MissingField { id: cto, field: "full name" }
+        );
+    }
+
+    #[test]
+    fn should_execute_scripted_commands() {
+        let mut directory = Directory::new();
+        let script = [
+            "Add Amir Khan to Executive as CEO",
+            "Add Sally Green to Engineering as CTO since 2020-03-01",
+            "Add Bola Ade to Engineering",
+            "Report 2 to 1",
+            "Report 3 to 2",
+        ];
+
+        for line in script {
+            execute(&mut directory, line.parse().unwrap(), date()).unwrap();
+        }
+
+        assert_eq!(directory.get(2).unwrap().start_date, Date::new(2020, 3, 1).unwrap());
+        assert_eq!(directory.get(3).unwrap().start_date, date());
+        assert_eq!(directory.get(3).unwrap().title, DEFAULT_TITLE);
+        assert_eq!(
+            execute(&mut directory, Command::Reports(1), date()).unwrap(),
+            vec!["#2 Sally Green (CTO, Engineering)", "#3 Bola Ade (Staff, Engineering)"]
+        );
+        assert_eq!(
+            execute(&mut directory, "Report 1 to 3".parse().unwrap(), date()),
+            Err(DirectoryError::ReportingCycle(vec![1, 3, 2, 1]))
+        );
+        assert_eq!(
+            execute(&mut directory, Command::ListAll, date()).unwrap(),
+            vec![
+                "Engineering:",
+                "  #3 Bola Ade (Staff, Engineering)",
+                "  #2 Sally Green (CTO, Engineering)",
+                "Executive:",
+                "  #1 Amir Khan (CEO, Executive)",
+            ]
+        );
+    }
+
+    #[test]
+    fn should_run_until_exit() {
+        let mut directory = Directory::new();
+        let input = "Add Sally to Engineering\nFire Sally\nList Engineering\nexit\nAdd Amir to Sales\n";
+        let mut output = Vec::new();
+
+        run(&mut directory, input.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(directory.len(), 1);
+        assert!(output.contains("Added Sally to Engineering with id 1"));
+        assert!(output.contains("Unrecognised command 'Fire Sally'"));
+        assert!(output.contains("#1 Sally (Staff, Engineering)"));
+    }
+
+    #[test]
+    fn should_parse_commands() {
+        assert_eq!(
+            "Add Sally to Engineering".parse::<Command>(),
+            Ok(Command::Add { name: "Sally".into(), department: "Engineering".into(), title: None, start_date: None })
+        );
+        assert_eq!(
+            "Add Amir Khan to Sales as Account Lead since 2023-05-01".parse::<Command>(),
+            Ok(Command::Add {
+                name: "Amir Khan".into(),
+                department: "Sales".into(),
+                title: Some("Account Lead".into()),
+                start_date: Date::new(2023, 5, 1),
+            })
+        );
+        assert_eq!("Report 3 to 1".parse::<Command>(), Ok(Command::Report { id: 3, manager: 1 }));
+        assert_eq!("List all".parse::<Command>(), Ok(Command::ListAll));
+        assert_eq!("List Human Resources".parse::<Command>(), Ok(Command::List("Human Resources".into())));
+        assert!("Add to Sales".parse::<Command>().is_err());
+        assert!("Fire Sally".parse::<Command>().is_err());
+    }
+}
@@ -0,0 +1,176 @@
+use std::error::Error;
+use std::fmt;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{Directory, DirectoryError, Employee};
+
+// On-disk layout of a saved directory.
+#[derive(Serialize, Deserialize)]
+struct DirectoryFile {
+    employees: Vec<Employee>,
+}
+
+#[derive(Debug)]
+pub enum StorageError {
+    Io(io::Error),
+    Format(serde_json::Error),
+    Invalid(DirectoryError),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageError::Io(e) => write!(f, "Could not access directory file: {}", e),
+            StorageError::Format(e) => write!(f, "Directory file is not valid JSON: {}", e),
+            StorageError::Invalid(e) => write!(f, "Directory file failed validation: {}", e),
+        }
+    }
+}
+
+impl Error for StorageError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            StorageError::Io(e) => Some(e),
+            StorageError::Format(e) => Some(e),
+            StorageError::Invalid(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for StorageError {
+    fn from(e: io::Error) -> StorageError {
+        StorageError::Io(e)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(e: serde_json::Error) -> StorageError {
+        StorageError::Format(e)
+    }
+}
+
+impl From<DirectoryError> for StorageError {
+    fn from(e: DirectoryError) -> StorageError {
+        StorageError::Invalid(e)
+    }
+}
+
+pub fn to_json(directory: &Directory) -> Result<String, StorageError> {
+    let file = DirectoryFile { employees: directory.employees().cloned().collect() };
+
+    Ok(serde_json::to_string_pretty(&file)?)
+}
+
+/// Parses a saved directory and validates it with [`Directory::from_employees`].
+pub fn from_json(json: &str) -> Result<Directory, StorageError> {
+    let file: DirectoryFile = serde_json::from_str(json)?;
+
+    Ok(Directory::from_employees(file.employees)?)
+}
+
+/// Writes the directory to a temporary file next to `path` first and then
+/// renames it over `path`, so a crash or a full disk part way through leaves
+/// the previous contents intact.
+pub fn save<P: AsRef<Path>>(directory: &Directory, path: P) -> Result<(), StorageError> {
+    let path = path.as_ref();
+    let json = to_json(directory)?;
+    let temp = temp_path(path);
+
+    let written = File::create(&temp).and_then(|mut file| {
+        file.write_all(json.as_bytes())?;
+        file.sync_all()
+    });
+    if let Err(e) = written.and_then(|()| fs::rename(&temp, path)) {
+        let _ = fs::remove_file(&temp);
+        return Err(e.into());
+    }
+
+    Ok(())
+}
+
+// `employees.json` becomes `employees.json.tmp` in the same directory, so the
+// rename never crosses file systems.
+fn temp_path(path: &Path) -> PathBuf {
+    let mut name = path.file_name().map(OsString::from).unwrap_or_default();
+    name.push(".tmp");
+    path.with_file_name(name)
+}
+
+pub fn load<P: AsRef<Path>>(path: P) -> Result<Directory, StorageError> {
+    from_json(&fs::read_to_string(path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::employee_system::Date;
+
+    #[test]
+    fn should_round_trip_through_a_file() {
+        let mut directory = Directory::new();
+        let start = Date::new(2022, 7, 4).unwrap();
+        let ceo = directory.hire("Amir Khan", "CEO", "Executive", start).unwrap();
+        let cto = directory.hire("Sally Green", "CTO", "Engineering", start).unwrap();
+        directory.set_manager(cto, Some(ceo)).unwrap();
+
+        let path = std::env::temp_dir().join(format!("employees-{}.json", std::process::id()));
+        save(&directory, &path).unwrap();
+        let loaded = load(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(
+            loaded.employees().collect::<Vec<_>>(),
+            directory.employees().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn should_replace_existing_files_without_leftovers() {
+        let path = std::env::temp_dir().join(format!("employees-replaced-{}.json", std::process::id()));
+        fs::write(&path, "old contents").unwrap();
+
+        save(&Directory::new(), &path).unwrap();
+        let saved = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert!(from_json(&saved).is_ok());
+        assert!(!temp_path(&path).exists());
+        assert!(matches!(save(&Directory::new(), "does/not/exist.json"), Err(StorageError::Io(_))));
+    }
+
+    #[test]
+    fn should_store_dates_as_text() {
+        let mut directory = Directory::new();
+        directory.hire("Sally", "Staff", "Engineering", Date::new(2021, 12, 1).unwrap()).unwrap();
+
+        assert!(to_json(&directory).unwrap().contains("\"start_date\": \"2021-12-01\""));
+    }
+
+    #[test]
+    fn should_reject_invalid_files() {
+        let bad_date = r#"{"employees": [{"id": 1, "full_name": "Sally", "title": "Staff",
+            "department": "Sales", "manager": null, "start_date": "2021-13-01"}]}"#;
+        let cycle = r#"{"employees": [
+            {"id": 1, "full_name": "Sally", "title": "Staff", "department": "Sales", "manager": 2, "start_date": "2021-01-01"},
+            {"id": 2, "full_name": "Amir", "title": "Staff", "department": "Sales", "manager": 1, "start_date": "2021-01-01"}]}"#;
+        let huge_id = r#"{"employees": [{"id": 4294967295, "full_name": "Sally", "title": "Staff",
+            "department": "Sales", "manager": null, "start_date": "2021-01-01"}]}"#;
+
+        assert!(matches!(from_json("not json"), Err(StorageError::Format(_))));
+        assert!(matches!(from_json(bad_date), Err(StorageError::Format(_))));
+        assert!(matches!(
+            from_json(cycle),
+            Err(StorageError::Invalid(DirectoryError::ReportingCycle(_)))
+        ));
+        assert!(matches!(
+            from_json(huge_id),
+            Err(StorageError::Invalid(DirectoryError::IdOutOfRange(u32::MAX)))
+        ));
+        assert!(matches!(load("does/not/exist.json"), Err(StorageError::Io(_))));
+    }
+}
@@ -1,6 +1,4 @@
-use std::collections::HashMap;
-
-use collections;
+use collections::employee_system::{self, storage};
 
 fn main() {
     // let mut v = vec![6, 10, 12, 15, 13, 3, 7, 9, 9, 9, 4, 8, 9, 15];
@@ -23,7 +21,23 @@ fn main() {
 
     // println!("{}", collections::pig_latin::optimized_transformer("yellow"));
 
-    let mut map = HashMap::new();
-    collections::employee_system::add(&mut map);
-    collections::employee_system::print_dept_employee(&map);
+    // Pass a JSON file path to keep the directory between runs.
+    let path = std::env::args().nth(1);
+
+    let mut directory = match &path {
+        Some(p) if std::path::Path::new(p).exists() => storage::load(p).unwrap_or_else(|e| {
+            eprintln!("{}", e);
+            std::process::exit(1);
+        }),
+        _ => employee_system::Directory::new(),
+    };
+
+    employee_system::add(&mut directory);
+    employee_system::print_dept_employee(&directory);
+
+    if let Some(p) = &path {
+        if let Err(e) = storage::save(&directory, p) {
+            eprintln!("{}", e);
+        }
+    }
 }
\ No newline at end of file
@@ -0,0 +1,521 @@
+use std::{
+    cell::{Cell, RefCell},
+    error::Error as StdError,
+    fmt,
+    future::Future,
+    thread,
+    time::{Duration, Instant},
+};
+
+/// A source of time for the combinators, so tests can run without sleeping.
+pub trait Clock {
+    /// Time elapsed since some fixed starting point.
+    fn now(&self) -> Duration;
+
+    fn sleep(&self, duration: Duration);
+}
+
+impl<C: Clock + ?Sized> Clock for &C {
+    fn now(&self) -> Duration {
+        (**self).now()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        (**self).sleep(duration)
+    }
+}
+
+/// The real clock: measures with [`Instant`] and blocks the thread to sleep.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    start: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> SystemClock {
+        SystemClock { start: Instant::now() }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> SystemClock {
+        SystemClock::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.start.elapsed()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        thread::sleep(duration)
+    }
+}
+
+/// A clock that only moves when told to. Sleeping advances it instantly and
+/// records the requested duration.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    now: Cell<Duration>,
+    sleeps: RefCell<Vec<Duration>>,
+}
+
+impl ManualClock {
+    pub fn new() -> ManualClock {
+        ManualClock::default()
+    }
+
+    pub fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+
+    /// Every duration passed to [`Clock::sleep`] so far, in order.
+    pub fn sleeps(&self) -> Vec<Duration> {
+        self.sleeps.borrow().clone()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        self.now.get()
+    }
+
+    fn sleep(&self, duration: Duration) {
+        self.sleeps.borrow_mut().push(duration);
+        self.advance(duration);
+    }
+}
+
+/// How long to wait between attempts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    None,
+    Fixed(Duration),
+    /// `initial * factor^n` before retry `n`, capped at `max`.
+    Exponential {
+        initial: Duration,
+        factor: u32,
+        max: Duration,
+    },
+}
+
+impl Backoff {
+    /// Doubling delays starting at `initial`, with no cap.
+    pub fn exponential(initial: Duration) -> Backoff {
+        Backoff::Exponential { initial, factor: 2, max: Duration::MAX }
+    }
+
+    /// The delay before retry number `retry`, counting from zero.
+    pub fn delay(&self, retry: u32) -> Duration {
+        match *self {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed(delay) => delay,
+            Backoff::Exponential { initial, factor, max } => {
+                initial.saturating_mul(factor.saturating_pow(retry)).min(max)
+            },
+        }
+    }
+}
+
+/// Re-runs a fallible operation until it succeeds or the retries run out.
+///
+/// ```
+/// use std::time::Duration;
+/// use err_handling::combinators::{Backoff, ManualClock, Retry};
+///
+/// let clock = ManualClock::new();
+/// let result: Result<u32, &str> = Retry::new(3)
+///     .backoff(Backoff::exponential(Duration::from_millis(10)))
+///     .clock(&clock)
+///     .run(|attempt| if attempt < 2 { Err("busy") } else { Ok(attempt) });
+///
+/// assert_eq!(result, Ok(2));
+/// assert_eq!(clock.sleeps(), vec![Duration::from_millis(10), Duration::from_millis(20)]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Retry<C = SystemClock> {
+    retries: u32,
+    backoff: Backoff,
+    deadline: Option<Duration>,
+    clock: C,
+}
+
+impl Retry<SystemClock> {
+    /// Allows up to `retries` more attempts after the first one fails.
+    pub fn new(retries: u32) -> Retry<SystemClock> {
+        Retry { retries, backoff: Backoff::None, deadline: None, clock: SystemClock::new() }
+    }
+}
+
+impl<C: Clock> Retry<C> {
+    pub fn backoff(mut self, backoff: Backoff) -> Retry<C> {
+        self.backoff = backoff;
+        self
+    }
+
+    /// Stops retrying once the next attempt would start later than `total`
+    /// after the first one.
+    pub fn deadline(mut self, total: Duration) -> Retry<C> {
+        self.deadline = Some(total);
+        self
+    }
+
+    pub fn clock<D: Clock>(self, clock: D) -> Retry<D> {
+        Retry { retries: self.retries, backoff: self.backoff, deadline: self.deadline, clock }
+    }
+
+    // Whether waiting `delay` more would start the next attempt past the
+    // deadline. A wait too long to even add up is past any deadline.
+    fn out_of_time(&self, start: Duration, delay: Duration) -> bool {
+        self.deadline.is_some_and(|deadline| {
+            (self.clock.now() - start).checked_add(delay).is_none_or(|next| next > deadline)
+        })
+    }
+
+    /// Runs `op`, passing the zero-based attempt number, and retries every error.
+    pub fn run<T, E, F>(&self, op: F) -> Result<T, E>
+    where
+        F: FnMut(u32) -> Result<T, E>,
+    {
+        self.run_if(|_| true, op)
+    }
+
+    /// Like [`Retry::run`], but returns errors for which `should_retry` is
+    /// false straight away.
+    pub fn run_if<T, E, P, F>(&self, should_retry: P, mut op: F) -> Result<T, E>
+    where
+        P: Fn(&E) -> bool,
+        F: FnMut(u32) -> Result<T, E>,
+    {
+        let start = self.clock.now();
+        let mut attempt = 0;
+
+        loop {
+            let error = match op(attempt) {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            match self.next_delay(start, attempt, &error, &should_retry) {
+                Some(delay) => self.clock.sleep(delay),
+                None => return Err(error),
+            }
+            attempt += 1;
+        }
+    }
+
+    /// Async version of [`Retry::run_if`]. The clock still tells the time,
+    /// but waits go through `sleep` so they do not block the thread, e.g.
+    /// `tokio::time::sleep`.
+    pub async fn run_if_async<T, E, P, F, Fut, S, SleepFut>(
+        &self,
+        should_retry: P,
+        mut op: F,
+        mut sleep: S,
+    ) -> Result<T, E>
+    where
+        P: Fn(&E) -> bool,
+        F: FnMut(u32) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+        S: FnMut(Duration) -> SleepFut,
+        SleepFut: Future<Output = ()>,
+    {
+        let start = self.clock.now();
+        let mut attempt = 0;
+
+        loop {
+            let error = match op(attempt).await {
+                Ok(value) => return Ok(value),
+                Err(e) => e,
+            };
+
+            match self.next_delay(start, attempt, &error, &should_retry) {
+                Some(delay) => sleep(delay).await,
+                None => return Err(error),
+            }
+            attempt += 1;
+        }
+    }
+
+    // How long to wait before retrying after `error`, or `None` to give up.
+    fn next_delay<E, P>(&self, start: Duration, attempt: u32, error: &E, should_retry: &P) -> Option<Duration>
+    where
+        P: Fn(&E) -> bool,
+    {
+        let delay = self.backoff.delay(attempt);
+
+        if attempt >= self.retries || self.out_of_time(start, delay) || !should_retry(error) {
+            None
+        } else {
+            Some(delay)
+        }
+    }
+}
+
+/// Tries each operation in turn and returns the first success, or every
+/// error if they all fail.
+pub struct Fallback<'a, T, E> {
+    ops: Vec<Box<dyn FnOnce() -> Result<T, E> + 'a>>,
+}
+
+impl<'a, T, E> Fallback<'a, T, E> {
+    pub fn new<F: FnOnce() -> Result<T, E> + 'a>(op: F) -> Fallback<'a, T, E> {
+        Fallback { ops: vec![Box::new(op)] }
+    }
+
+    pub fn or<F: FnOnce() -> Result<T, E> + 'a>(mut self, op: F) -> Fallback<'a, T, E> {
+        self.ops.push(Box::new(op));
+        self
+    }
+
+    pub fn run(self) -> Result<T, Vec<E>> {
+        let mut errors = Vec::new();
+
+        for op in self.ops {
+            match op() {
+                Ok(value) => return Ok(value),
+                Err(e) => errors.push(e),
+            }
+        }
+
+        Err(errors)
+    }
+}
+
+/// Returned by [`timeout`] when an operation overran its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Elapsed {
+    pub limit: Duration,
+    pub took: Duration,
+}
+
+impl fmt::Display for Elapsed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "operation took {:?}, limit was {:?}", self.took, self.limit)
+    }
+}
+
+impl StdError for Elapsed {}
+
+/// Runs `op` and turns its result into an [`Elapsed`] error if it took longer
+/// than `limit`.
+///
+/// `op` is not interrupted: this is for operations that finish eventually but
+/// whose results are useless when late. Blocking I/O should use its own
+/// timeouts instead, as [`crate::http`] does.
+pub fn timeout<T, E, C, F>(clock: &C, limit: Duration, op: F) -> Result<T, E>
+where
+    E: From<Elapsed>,
+    C: Clock,
+    F: FnOnce() -> Result<T, E>,
+{
+    let start = clock.now();
+    let result = op();
+    let took = clock.now() - start;
+
+    if took > limit {
+        return Err(Elapsed { limit, took }.into());
+    }
+
+    result
+}
+
+/// Splits results into every success or, if any failed, every error.
+pub fn aggregate<T, E, I>(results: I) -> Result<Vec<T>, Vec<E>>
+where
+    I: IntoIterator<Item = Result<T, E>>,
+{
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+
+    for result in results {
+        match result {
+            Ok(value) => values.push(value),
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(values)
+    } else {
+        Err(errors)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_number, Error, ErrorKind};
+
+    const MS: Duration = Duration::from_millis(1);
+
+    #[test]
+    fn should_compute_backoff_delays() {
+        let capped = Backoff::Exponential { initial: MS * 10, factor: 3, max: MS * 100 };
+
+        assert_eq!(Backoff::None.delay(5), Duration::ZERO);
+        assert_eq!(Backoff::Fixed(MS * 5).delay(5), MS * 5);
+        assert_eq!((0..4).map(|n| capped.delay(n)).collect::<Vec<_>>(), vec![MS * 10, MS * 30, MS * 90, MS * 100]);
+        assert_eq!(Backoff::exponential(MS).delay(64), MS * u32::MAX);
+    }
+
+    #[test]
+    fn should_retry_until_success_with_backoff() {
+        let clock = ManualClock::new();
+        let retry = Retry::new(5).backoff(Backoff::exponential(MS)).clock(&clock);
+
+        let result: Result<&str, &str> = retry.run(|attempt| if attempt < 3 { Err("busy") } else { Ok("done") });
+
+        assert_eq!(result, Ok("done"));
+        assert_eq!(clock.sleeps(), vec![MS, MS * 2, MS * 4]);
+    }
+
+    #[test]
+    fn should_return_last_error_when_retries_run_out() {
+        let clock = ManualClock::new();
+        let mut calls = 0;
+
+        let result: Result<(), u32> = Retry::new(2).backoff(Backoff::Fixed(MS)).clock(&clock).run(|attempt| {
+            calls += 1;
+            Err(attempt)
+        });
+
+        assert_eq!(result, Err(2));
+        assert_eq!(calls, 3);
+        assert_eq!(clock.now(), MS * 2);
+    }
+
+    #[test]
+    fn should_not_retry_when_predicate_rejects_error() {
+        let clock = ManualClock::new();
+
+        let result: Result<(), &str> = Retry::new(5)
+            .clock(&clock)
+            .run_if(|e| *e == "busy", |attempt| if attempt == 0 { Err("busy") } else { Err("fatal") });
+
+        assert_eq!(result, Err("fatal"));
+        assert_eq!(clock.sleeps().len(), 1);
+    }
+
+    #[test]
+    fn should_stop_retrying_at_deadline() {
+        let clock = ManualClock::new();
+
+        let result: Result<(), ()> = Retry::new(10)
+            .backoff(Backoff::exponential(MS * 10))
+            .deadline(MS * 50)
+            .clock(&clock)
+            .run(|_| {
+                clock.advance(MS * 5);
+                Err(())
+            });
+
+        assert!(result.is_err());
+        // 5 + 10 + 5 + 20 + 5 = 45ms used; the next 40ms delay would pass 50ms.
+        assert_eq!(clock.sleeps(), vec![MS * 10, MS * 20]);
+        assert_eq!(clock.now(), MS * 45);
+    }
+
+    #[test]
+    fn should_treat_overflowing_delays_as_past_the_deadline() {
+        let clock = ManualClock::new();
+
+        // A saturated exponential backoff waits `Duration::MAX` too.
+        for backoff in [Backoff::Fixed(Duration::MAX), Backoff::exponential(Duration::MAX)] {
+            let mut calls = 0;
+            let result: Result<(), ()> = Retry::new(3).backoff(backoff).deadline(MS * 50).clock(&clock).run(|_| {
+                calls += 1;
+                clock.advance(MS);
+                Err(())
+            });
+
+            assert!(result.is_err());
+            assert_eq!(calls, 1);
+        }
+        assert!(clock.sleeps().is_empty());
+    }
+
+    #[tokio::test]
+    async fn should_retry_async_with_the_same_policy() {
+        let clock = ManualClock::new();
+        let retry = Retry::new(5).backoff(Backoff::exponential(MS * 10)).deadline(MS * 25).clock(&clock);
+        let mut calls = 0;
+
+        let result: Result<(), &str> = retry
+            .run_if_async(
+                |e| *e != "fatal",
+                |_| {
+                    calls += 1;
+                    async { Err("busy") }
+                },
+                |delay| {
+                    clock.sleep(delay);
+                    async {}
+                },
+            )
+            .await;
+
+        assert_eq!(result, Err("busy"));
+        // The third attempt would have to start at 10 + 20 = 30ms, past 25ms.
+        assert_eq!(calls, 2);
+        assert_eq!(clock.sleeps(), vec![MS * 10]);
+
+        let fatal: Result<(), &str> = retry.run_if_async(|e| *e != "fatal", |_| async { Err("fatal") }, |_| async {}).await;
+        assert_eq!(fatal, Err("fatal"));
+    }
+
+    #[test]
+    fn should_fall_back_in_order() {
+        let mut tried = Vec::new();
+
+        let result = Fallback::new(|| {
+            tried.push("primary");
+            parse_number::<i32>("oops").map_err(|e| Error::parse("oops", 1, e))
+        })
+        .or(|| Err(Error::EmptyInput))
+        .or(|| Ok(3))
+        .or(|| panic!("not reached"))
+        .run();
+
+        assert_eq!(result.unwrap(), 3);
+        assert_eq!(tried, vec!["primary"]);
+    }
+
+    #[test]
+    fn should_collect_errors_when_every_fallback_fails() {
+        let errors = Fallback::new(|| Err::<(), _>(Error::EmptyInput))
+            .or(|| std::fs::read_to_string("missing.txt").map(|_| ()).map_err(Error::from))
+            .run()
+            .unwrap_err();
+
+        let error = Error::from(errors);
+        assert_eq!(error.kind(), ErrorKind::Multiple);
+        assert_eq!(error.to_string(), "2 errors occurred");
+    }
+
+    #[test]
+    fn should_time_out_slow_operations() {
+        let clock = ManualClock::new();
+
+        let fast: Result<i32, Error> = timeout(&clock, MS * 10, || {
+            clock.advance(MS * 10);
+            Ok(1)
+        });
+        let slow: Result<i32, Error> = timeout(&clock, MS * 10, || {
+            clock.advance(MS * 11);
+            Ok(1)
+        });
+
+        assert_eq!(fast.unwrap(), 1);
+        let err = slow.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::Timeout);
+        assert!(matches!(err, Error::Timeout(Elapsed { took, .. }) if took == MS * 11));
+    }
+
+    #[test]
+    fn should_aggregate_results() {
+        assert_eq!(aggregate(vec![Ok::<_, &str>(1), Ok(2)]), Ok(vec![1, 2]));
+        assert_eq!(aggregate(vec![Ok(1), Err("a"), Ok(3), Err("b")]), Err(vec!["a", "b"]));
+    }
+}
@@ -0,0 +1,304 @@
+use std::{
+    collections::HashMap,
+    fs::File,
+    io::{BufRead, BufReader},
+    path::{Path, PathBuf},
+};
+
+use crate::{Error, ParseError};
+
+/// A range of characters on one line: `line` counts from one, the columns
+/// from zero, and `end` is exclusive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub line: usize,
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Style {
+    Plain,
+    /// Colours headers, gutters and carets with ANSI escape codes.
+    Ansi,
+}
+
+/// A compiler-style report pointing at the offending text in a source line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub message: String,
+    pub path: Option<PathBuf>,
+    pub span: Span,
+    pub source_line: String,
+    pub help: Option<String>,
+}
+
+const RED: &str = "\x1b[1;31m";
+const BLUE: &str = "\x1b[1;34m";
+const CYAN: &str = "\x1b[1;36m";
+const BOLD: &str = "\x1b[1m";
+const RESET: &str = "\x1b[0m";
+
+impl Diagnostic {
+    /// Renders the diagnostic, e.g.
+    ///
+    /// ```text
+    /// error: Invalid digit 'x' at position 3
+    ///  --> numbers.txt:2:4
+    ///   |
+    /// 2 |   1x3
+    ///   |    ^
+    ///   |
+    ///   = help: numbers may only contain digits, a sign, a 0x/0o/0b prefix and `_` separators
+    /// ```
+    pub fn render(&self, style: Style) -> String {
+        let paint = |color: &str, text: &str| match style {
+            Style::Plain => text.to_owned(),
+            Style::Ansi => format!("{}{}{}", color, text, RESET),
+        };
+
+        let line_number = self.span.line.to_string();
+        let pad = " ".repeat(line_number.len());
+        let gutter = paint(BLUE, &format!("{} |", pad));
+        let location = match &self.path {
+            Some(path) => format!("{}:{}:{}", path.display(), self.span.line, self.span.start + 1),
+            None => format!("<input>:{}:{}", self.span.line, self.span.start + 1),
+        };
+        let width = self.span.end.saturating_sub(self.span.start).max(1);
+        // Tabs are copied so the carets line up however wide they display.
+        let indent: String = self
+            .source_line
+            .chars()
+            .chain(std::iter::repeat(' '))
+            .take(self.span.start)
+            .map(|c| if c == '\t' { '\t' } else { ' ' })
+            .collect();
+
+        let mut out = format!("{}{}\n", paint(RED, "error"), paint(BOLD, &format!(": {}", self.message)));
+        out.push_str(&format!("{}{} {}\n", pad, paint(BLUE, "-->"), location));
+        out.push_str(&format!("{}\n", gutter));
+        out.push_str(&format!("{} {}\n", paint(BLUE, &format!("{} |", line_number)), self.source_line));
+        out.push_str(&format!("{} {}{}\n", gutter, indent, paint(RED, &"^".repeat(width))));
+
+        if let Some(help) = &self.help {
+            out.push_str(&format!("{}\n", gutter));
+            out.push_str(&format!("{} {} {}\n", pad, paint(BLUE, "="), paint(CYAN, &format!("help: {}", help))));
+        }
+
+        out
+    }
+}
+
+fn help_for(error: &ParseError) -> Option<String> {
+    match error {
+        ParseError::InvalidDigit { .. } => Some(
+            "numbers may only contain digits, a sign, a 0x/0o/0b prefix and `_` separators".to_owned(),
+        ),
+        ParseError::Overflow => Some("the value does not fit in the target number type".to_owned()),
+        ParseError::InvalidFormat => Some("expected a number such as `42`, `-7`, `0xff` or `1.5e3`".to_owned()),
+        ParseError::EmptyString => None,
+    }
+}
+
+// Walks the error tree collecting each parse failure with the nearest file it
+// was found in.
+fn collect<'a>(error: &'a Error, path: Option<&'a Path>, found: &mut Vec<(Option<&'a Path>, &'a Error)>) {
+    match error {
+        Error::File { path, source } => collect(source, Some(path), found),
+        Error::Context { source, .. } => collect(source, path, found),
+        Error::Multiple(errors) => errors.iter().for_each(|e| collect(e, path, found)),
+        Error::Parse { .. } => found.push((path, error)),
+        _ => {},
+    }
+}
+
+// Reads just the wanted lines of `path` in one pass.
+fn read_lines(path: &Path, wanted: &[usize]) -> HashMap<usize, String> {
+    let Ok(file) = File::open(path) else {
+        return HashMap::new();
+    };
+
+    BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .enumerate()
+        .map(|(index, text)| (index + 1, text))
+        .filter(|(line, _)| wanted.contains(line))
+        .take(wanted.len())
+        .collect()
+}
+
+/// Builds a [`Diagnostic`] for every parse failure inside `error`.
+///
+/// Source lines are re-read from the file named by the nearest
+/// [`Error::File`]; when that is missing, unreadable or no longer has the
+/// offending text where it was, that text is shown on its own at its original
+/// column.
+pub fn diagnostics(error: &Error) -> Vec<Diagnostic> {
+    let mut found = Vec::new();
+    collect(error, None, &mut found);
+
+    let mut wanted: HashMap<&Path, Vec<usize>> = HashMap::new();
+    for (path, error) in &found {
+        if let (Some(path), Error::Parse { line, .. }) = (path, error) {
+            wanted.entry(path).or_default().push(*line);
+        }
+    }
+    let sources: HashMap<&Path, HashMap<usize, String>> =
+        wanted.iter().map(|(path, lines)| (*path, read_lines(path, lines))).collect();
+
+    found
+        .into_iter()
+        .filter_map(|(path, error)| {
+            let Error::Parse { text, line, column, source } = error else {
+                return None;
+            };
+
+            let source_line = path
+                .and_then(|p| sources.get(p))
+                .and_then(|lines| lines.get(line))
+                .filter(|current| current.chars().skip(*column).take(text.chars().count()).eq(text.chars()))
+                .cloned()
+                .unwrap_or_else(|| format!("{}{}", " ".repeat(*column), text));
+
+            let span = match source {
+                ParseError::InvalidDigit { position, .. } => Span { line: *line, start: *position, end: position + 1 },
+                _ => Span { line: *line, start: *column, end: column + text.chars().count() },
+            };
+
+            Some(Diagnostic {
+                message: source.to_string(),
+                path: path.map(Path::to_path_buf),
+                span,
+                source_line,
+                help: help_for(source),
+            })
+        })
+        .collect()
+}
+
+/// Renders every parse failure in `error` as a diagnostic, falling back to
+/// [`Error::report`] for errors that do not point at any source text.
+pub fn render_error(error: &Error, style: Style) -> String {
+    let diagnostics = diagnostics(error);
+
+    if diagnostics.is_empty() {
+        return format!("{}\n", error.report());
+    }
+
+    diagnostics.iter().map(|d| d.render(style)).collect::<Vec<_>>().join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+
+    use super::*;
+    use crate::{read_file_and_parse_all, Mode};
+
+    fn write_temp(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}-{}", std::process::id(), name));
+        fs::write(&path, contents).unwrap();
+        path
+    }
+
+    #[test]
+    fn should_point_at_invalid_digit() {
+        let path = write_temp("digits.txt", "10\n  1x3 # typo\n");
+
+        let err = read_file_and_parse_all::<i32, _>(&path, Mode::CollectAll).unwrap_err();
+        let rendered = render_error(&err, Style::Plain);
+        fs::remove_file(&path).unwrap();
+
+        let expected = format!(
+            "\
+error: Invalid digit 'x' at position 3
+ --> {}:2:4
+  |
+2 |   1x3 # typo
+  |    ^
+  |
+  = help: numbers may only contain digits, a sign, a 0x/0o/0b prefix and `_` separators
+",
+            path.display()
+        );
+        assert_eq!(rendered, expected);
+    }
+
+    #[test]
+    fn should_underline_whole_number_on_overflow() {
+        let path = write_temp("overflow.txt", "1\n2\n3\n4\n5\n6\n7\n8\n9\n   300\n");
+
+        let err = read_file_and_parse_all::<u8, _>(&path, Mode::FailFast).unwrap_err();
+        let found = diagnostics(&err);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].span, Span { line: 10, start: 3, end: 6 });
+        assert!(found[0].render(Style::Plain).contains("10 |    300\n   |    ^^^\n"));
+    }
+
+    #[test]
+    fn should_render_every_error_in_collect_all_mode() {
+        let err = crate::reader::parse_all::<i32, _>("1\nx\n3\n0b2\n".as_bytes(), Mode::CollectAll).unwrap_err();
+
+        let found = diagnostics(&err);
+
+        assert_eq!(found.iter().map(|d| d.span.line).collect::<Vec<_>>(), vec![2, 4]);
+        assert_eq!(found[1].path, None);
+        assert_eq!(found[1].source_line, "0b2");
+        assert!(found[1].render(Style::Plain).starts_with("error: Invalid digit '2' at position 2\n --> <input>:4:3\n"));
+    }
+
+    #[test]
+    fn should_keep_tabs_in_caret_indentation() {
+        let diagnostic = Diagnostic {
+            message: "Invalid digit 'x' at position 3".to_owned(),
+            path: None,
+            span: Span { line: 1, start: 3, end: 4 },
+            source_line: "\t\t1x3".to_owned(),
+            help: None,
+        };
+
+        assert!(diagnostic.render(Style::Plain).contains("1 | \t\t1x3\n  | \t\t ^\n"));
+    }
+
+    #[test]
+    fn should_not_show_lines_changed_since_parsing() {
+        let path = write_temp("edited.txt", "1\n  1x3\n");
+
+        let err = read_file_and_parse_all::<i32, _>(&path, Mode::CollectAll).unwrap_err();
+        fs::write(&path, "1\n2\n").unwrap();
+        let found = diagnostics(&err);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(found[0].source_line, "  1x3");
+    }
+
+    #[test]
+    fn should_colour_output_in_ansi_mode() {
+        let diagnostic = Diagnostic {
+            message: "Number out of range for target type".to_owned(),
+            path: None,
+            span: Span { line: 1, start: 0, end: 3 },
+            source_line: "999".to_owned(),
+            help: None,
+        };
+
+        let rendered = diagnostic.render(Style::Ansi);
+
+        assert!(rendered.starts_with("\x1b[1;31merror\x1b[0m\x1b[1m: Number out of range"));
+        assert!(rendered.contains("\x1b[1;31m^^^\x1b[0m"));
+        assert!(!diagnostic.render(Style::Plain).contains('\x1b'));
+    }
+
+    #[test]
+    fn should_fall_back_to_report_without_source_text() {
+        let err = Error::EmptyInput.context("while reading totals.txt");
+
+        assert_eq!(
+            render_error(&err, Style::Plain),
+            "error: while reading totals.txt\ncaused by: input is empty or has no valid lines\n"
+        );
+    }
+}
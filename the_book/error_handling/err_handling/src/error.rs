@@ -0,0 +1,270 @@
+use std::{
+    error::Error as StdError,
+    fmt, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{combinators::Elapsed, ParseError};
+
+/// What went wrong, without any of the context attached on the way up.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorKind {
+    Io,
+    Parse,
+    EmptyInput,
+    Multiple,
+    Http,
+    Transport,
+    Timeout,
+}
+
+/// The error type returned by the file and parsing functions in this crate.
+///
+/// Use [`Error::kind`] to match on the underlying failure and [`Error::report`]
+/// to print it together with every cause.
+#[derive(Debug)]
+pub enum Error {
+    Io(io::Error),
+    Parse {
+        text: String,
+        line: usize,
+        /// Character column where `text` starts, counting from zero.
+        column: usize,
+        source: ParseError,
+    },
+    EmptyInput,
+    /// Several independent failures, e.g. every bad line in a file.
+    Multiple(Vec<Error>),
+    /// The server answered with a non-success status.
+    Http {
+        url: String,
+        status: u16,
+    },
+    /// The request could not be completed: connection failure, timeout, etc.
+    Transport(reqwest::Error),
+    /// An operation wrapped in [`crate::combinators::timeout`] overran.
+    Timeout(Elapsed),
+    Context {
+        context: String,
+        source: Box<Error>,
+    },
+    /// Marks the file that `source` came from, for [`crate::diagnostic`].
+    File {
+        path: PathBuf,
+        source: Box<Error>,
+    },
+}
+
+impl Error {
+    /// A parse error for `line_text`, which is trimmed for display while its
+    /// leading whitespace sets the column.
+    pub fn parse(line_text: &str, line: usize, source: ParseError) -> Error {
+        let text = line_text.trim();
+        let column = line_text[..line_text.len() - line_text.trim_start().len()].chars().count();
+
+        Error::Parse { text: text.to_owned(), line, column, source }
+    }
+
+    /// Wraps the error with a note about what was being done when it happened.
+    pub fn context<C: Into<String>>(self, context: C) -> Error {
+        Error::Context { context: context.into(), source: Box::new(self) }
+    }
+
+    /// Records that the error happened while parsing the file at `path`.
+    pub fn in_file<P: AsRef<Path>>(self, path: P) -> Error {
+        Error::File { path: path.as_ref().to_path_buf(), source: Box::new(self) }
+    }
+
+    pub fn kind(&self) -> ErrorKind {
+        match self.root() {
+            Error::Io(_) => ErrorKind::Io,
+            Error::Parse { .. } => ErrorKind::Parse,
+            Error::EmptyInput => ErrorKind::EmptyInput,
+            Error::Multiple(_) => ErrorKind::Multiple,
+            Error::Http { .. } => ErrorKind::Http,
+            Error::Transport(_) => ErrorKind::Transport,
+            Error::Timeout(_) => ErrorKind::Timeout,
+            Error::Context { .. } | Error::File { .. } => unreachable!("root() never returns a context"),
+        }
+    }
+
+    /// The innermost error once all context and file layers are peeled off.
+    pub fn root(&self) -> &Error {
+        let mut error = self;
+        while let Error::Context { source, .. } | Error::File { source, .. } = error {
+            error = source;
+        }
+        error
+    }
+
+    /// This error followed by each of its causes.
+    pub fn chain(&self) -> Chain<'_> {
+        Chain { next: Some(self) }
+    }
+
+    /// Renders the error and its causes, one per line.
+    ///
+    /// For [`Error::Multiple`] each inner error is reported in turn, indented.
+    pub fn report(&self) -> String {
+        let mut report = String::new();
+
+        for (index, error) in self.chain().enumerate() {
+            if index == 0 {
+                report.push_str(&format!("error: {}", error));
+            } else {
+                report.push_str(&format!("\ncaused by: {}", error));
+            }
+        }
+
+        if let Error::Multiple(errors) = self.root() {
+            for error in errors {
+                for line in error.report().lines() {
+                    report.push_str(&format!("\n  {}", line));
+                }
+            }
+        }
+
+        report
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Io(_) => write!(f, "I/O error"),
+            Error::Parse { text, line, .. } => write!(f, "could not parse '{}' on line {}", text, line),
+            Error::EmptyInput => write!(f, "input is empty or has no valid lines"),
+            Error::Multiple(errors) => write!(f, "{} errors occurred", errors.len()),
+            Error::Http { url, status } => write!(f, "{} responded with status {}", url, status),
+            Error::Transport(_) => write!(f, "request failed"),
+            Error::Timeout(elapsed) => write!(f, "timed out: {}", elapsed),
+            Error::Context { context, .. } => write!(f, "{}", context),
+            Error::File { path, .. } => write!(f, "in {}", path.display()),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Error::Io(e) => Some(e),
+            Error::Parse { source, .. } => Some(source),
+            Error::Transport(e) => Some(e),
+            Error::EmptyInput | Error::Multiple(_) | Error::Http { .. } | Error::Timeout(_) => None,
+            Error::Context { source, .. } | Error::File { source, .. } => Some(source.as_ref()),
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(e: io::Error) -> Error {
+        Error::Io(e)
+    }
+}
+
+impl From<Elapsed> for Error {
+    fn from(e: Elapsed) -> Error {
+        Error::Timeout(e)
+    }
+}
+
+impl From<Vec<Error>> for Error {
+    fn from(errors: Vec<Error>) -> Error {
+        Error::Multiple(errors)
+    }
+}
+
+impl From<reqwest::Error> for Error {
+    fn from(e: reqwest::Error) -> Error {
+        Error::Transport(e)
+    }
+}
+
+/// Iterator over an error and its `source()` chain.
+pub struct Chain<'a> {
+    next: Option<&'a (dyn StdError + 'static)>,
+}
+
+impl<'a> Iterator for Chain<'a> {
+    type Item = &'a (dyn StdError + 'static);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let current = self.next?;
+        self.next = current.source();
+        Some(current)
+    }
+}
+
+/// Adds `.context(..)` to any result whose error converts into [`Error`].
+pub trait Context<T> {
+    fn context<C: Into<String>>(self, context: C) -> Result<T, Error>;
+
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> Result<T, Error>;
+}
+
+impl<T, E: Into<Error>> Context<T> for Result<T, E> {
+    fn context<C: Into<String>>(self, context: C) -> Result<T, Error> {
+        self.map_err(|e| e.into().context(context))
+    }
+
+    fn with_context<C: Into<String>, F: FnOnce() -> C>(self, f: F) -> Result<T, Error> {
+        self.map_err(|e| e.into().context(f()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_see_through_context_when_matching_kind() {
+        let error = Error::parse("abc", 3, ParseError::InvalidFormat)
+            .context("while parsing totals")
+            .context("while reading config.txt");
+
+        assert_eq!(error.kind(), ErrorKind::Parse);
+        assert!(matches!(error.root(), Error::Parse { line: 3, .. }));
+    }
+
+    #[test]
+    fn should_report_full_causal_chain() {
+        let result: Result<(), io::Error> = Err(io::Error::new(io::ErrorKind::NotFound, "file not found"));
+
+        let error = result.context("while reading config.txt").unwrap_err();
+
+        assert_eq!(error.chain().count(), 3);
+        assert_eq!(
+            error.report(),
+            "error: while reading config.txt\ncaused by: I/O error\ncaused by: file not found"
+        );
+    }
+
+    #[test]
+    fn should_report_every_inner_error() {
+        let error = Error::Multiple(vec![
+            Error::parse("abc", 2, ParseError::InvalidFormat),
+            Error::parse("x", 4, ParseError::InvalidFormat),
+        ])
+        .context("while reading totals.txt");
+
+        assert_eq!(error.kind(), ErrorKind::Multiple);
+        assert_eq!(
+            error.report(),
+            "error: while reading totals.txt\n\
+             caused by: 2 errors occurred\n  \
+             error: could not parse 'abc' on line 2\n  \
+             caused by: Invalid number format\n  \
+             error: could not parse 'x' on line 4\n  \
+             caused by: Invalid number format"
+        );
+    }
+
+    #[test]
+    fn should_build_context_lazily() {
+        let ok: Result<i32, Error> = Ok(1);
+        let err: Result<i32, Error> = Err(Error::EmptyInput);
+
+        assert_eq!(ok.with_context(|| -> String { panic!("not called on success") }).unwrap(), 1);
+        assert_eq!(err.with_context(|| format!("while reading {}", "a.txt")).unwrap_err().to_string(), "while reading a.txt");
+    }
+}
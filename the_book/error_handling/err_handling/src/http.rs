@@ -0,0 +1,282 @@
+use std::time::Duration;
+
+use crate::{
+    combinators::{Backoff, Retry},
+    parse_first_line, Context, Error,
+};
+
+/// Timeout and retry settings for [`fetch_and_parse`] and [`fetch_and_parse_async`].
+#[derive(Debug, Clone)]
+pub struct FetchOptions {
+    /// Limit for a single attempt, from connecting to reading the whole body.
+    pub timeout: Duration,
+    /// How many more attempts to make after a retryable failure.
+    pub retries: u32,
+    /// Delay before the first retry, doubled before each one after that.
+    pub backoff: Duration,
+}
+
+impl Default for FetchOptions {
+    fn default() -> FetchOptions {
+        FetchOptions {
+            timeout: Duration::from_secs(10),
+            retries: 3,
+            backoff: Duration::from_millis(200),
+        }
+    }
+}
+
+impl FetchOptions {
+    // The policy both the blocking and the async fetch retry with.
+    fn retry(&self) -> Retry {
+        Retry::new(self.retries).backoff(Backoff::exponential(self.backoff))
+    }
+}
+
+// Connection problems, timeouts, rate limiting and server errors may go away
+// on their own; anything else, such as a malformed URL, an undecodable body or
+// too many redirects, will fail the same way again.
+fn is_retryable(error: &Error) -> bool {
+    match error {
+        Error::Transport(e) => e.is_connect() || e.is_timeout() || e.is_request(),
+        Error::Http { status, .. } => *status >= 500 || *status == 429,
+        _ => false,
+    }
+}
+
+fn check_status(url: &str, status: reqwest::StatusCode) -> Result<(), Error> {
+    if status.is_success() {
+        Ok(())
+    } else {
+        Err(Error::Http { url: url.to_owned(), status: status.as_u16() })
+    }
+}
+
+fn finish(url: &str, attempts: u32, body: Result<String, Error>) -> Result<i32, Error> {
+    let plural = if attempts == 1 { "" } else { "s" };
+    let body = body.with_context(|| format!("while fetching {} ({} attempt{})", url, attempts, plural))?;
+
+    parse_first_line(&body).with_context(|| format!("while parsing response from {}", url))
+}
+
+fn fetch_once(client: &reqwest::blocking::Client, url: &str) -> Result<String, Error> {
+    let response = client.get(url).send()?;
+    check_status(url, response.status())?;
+
+    Ok(response.text()?)
+}
+
+/// Fetches `url` and parses the first line of the body, like
+/// [`crate::read_file_and_parse`] does for files.
+///
+/// Connection failures, timeouts, `429` and `5xx` responses are retried with
+/// exponential backoff; other statuses, invalid URLs and parse errors are
+/// returned straight away.
+///
+/// # Panics
+///
+/// Panics if called from inside a Tokio runtime, because the blocking client
+/// starts a runtime of its own. Use [`fetch_and_parse_async`] there instead.
+pub fn fetch_and_parse(url: &str, options: &FetchOptions) -> Result<i32, Error> {
+    let client = reqwest::blocking::Client::builder().timeout(options.timeout).build()?;
+    let mut attempts = 0;
+
+    let body = options.retry().run_if(is_retryable, |attempt| {
+        attempts = attempt + 1;
+        fetch_once(&client, url)
+    });
+
+    finish(url, attempts, body)
+}
+
+async fn fetch_once_async(client: &reqwest::Client, url: &str) -> Result<String, Error> {
+    let response = client.get(url).send().await?;
+    check_status(url, response.status())?;
+
+    Ok(response.text().await?)
+}
+
+/// Async version of [`fetch_and_parse`], with the same retry policy. Must be
+/// polled inside a Tokio runtime.
+pub async fn fetch_and_parse_async(url: &str, options: &FetchOptions) -> Result<i32, Error> {
+    let client = reqwest::Client::builder().timeout(options.timeout).build()?;
+    let mut attempts = 0;
+
+    let body = options
+        .retry()
+        .run_if_async(
+            is_retryable,
+            |attempt| {
+                attempts = attempt + 1;
+                fetch_once_async(&client, url)
+            },
+            tokio::time::sleep,
+        )
+        .await;
+
+    finish(url, attempts, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        io::{BufRead, BufReader, Write},
+        net::TcpListener,
+        thread,
+        sync::{
+            atomic::{AtomicUsize, Ordering},
+            Arc,
+        },
+    };
+
+    use super::*;
+    use crate::{ErrorKind, ParseError};
+
+    struct Reply {
+        status: u16,
+        body: &'static str,
+        delay: Duration,
+    }
+
+    fn reply(status: u16, body: &'static str) -> Reply {
+        Reply { status, body, delay: Duration::ZERO }
+    }
+
+    struct StubServer {
+        url: String,
+        hits: Arc<AtomicUsize>,
+    }
+
+    // Answers one connection per scripted reply, in order, then stops listening.
+    fn serve(replies: Vec<Reply>) -> StubServer {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/number", listener.local_addr().unwrap());
+        let hits = Arc::new(AtomicUsize::new(0));
+        let counter = Arc::clone(&hits);
+
+        thread::spawn(move || {
+            for reply in replies {
+                let (mut stream, _) = listener.accept().unwrap();
+                counter.fetch_add(1, Ordering::SeqCst);
+
+                let mut reader = BufReader::new(stream.try_clone().unwrap());
+                let mut line = String::new();
+                while reader.read_line(&mut line).unwrap_or(0) > 2 {
+                    line.clear();
+                }
+
+                thread::sleep(reply.delay);
+                let response = format!(
+                    "HTTP/1.1 {} Stub\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+                    reply.status,
+                    reply.body.len(),
+                    reply.body
+                );
+                let _ = stream.write_all(response.as_bytes());
+            }
+        });
+
+        StubServer { url, hits }
+    }
+
+    fn options(retries: u32) -> FetchOptions {
+        FetchOptions {
+            timeout: Duration::from_millis(500),
+            retries,
+            backoff: Duration::from_millis(1),
+        }
+    }
+
+    #[test]
+    fn should_fetch_and_parse_first_line() {
+        let server = serve(vec![reply(200, "42\nignored\n")]);
+
+        assert_eq!(fetch_and_parse(&server.url, &options(0)).unwrap(), 42);
+    }
+
+    #[test]
+    fn should_retry_server_errors_until_success() {
+        let server = serve(vec![reply(503, ""), reply(500, ""), reply(200, "7")]);
+
+        assert_eq!(fetch_and_parse(&server.url, &options(3)).unwrap(), 7);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 3);
+    }
+
+    #[test]
+    fn should_not_retry_client_errors() {
+        let server = serve(vec![reply(404, "missing"), reply(200, "1")]);
+
+        let err = fetch_and_parse(&server.url, &options(3)).unwrap_err();
+
+        assert!(matches!(err.root(), Error::Http { status: 404, .. }));
+        assert_eq!(err.to_string(), format!("while fetching {} (1 attempt)", server.url));
+        assert_eq!(server.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn should_give_up_after_retries() {
+        let server = serve(vec![reply(502, ""), reply(502, "")]);
+
+        let err = fetch_and_parse(&server.url, &options(1)).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Http);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 2);
+    }
+
+    #[test]
+    fn should_map_parse_failures() {
+        let server = serve(vec![reply(200, "forty two")]);
+
+        let err = fetch_and_parse(&server.url, &options(3)).unwrap_err();
+
+        assert!(matches!(
+            err.root(),
+            Error::Parse { line: 1, source: ParseError::InvalidDigit { position: 0, found: 'f' }, .. }
+        ));
+        assert_eq!(server.hits.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn should_time_out_slow_responses() {
+        let server = serve(vec![Reply { status: 200, body: "1", delay: Duration::from_secs(2) }]);
+        let options = FetchOptions { timeout: Duration::from_millis(100), ..options(0) };
+
+        let err = fetch_and_parse(&server.url, &options).unwrap_err();
+
+        match err.root() {
+            Error::Transport(e) => assert!(e.is_timeout()),
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_report_connection_failures_as_transport_errors() {
+        let url = {
+            let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+            format!("http://{}/", listener.local_addr().unwrap())
+        };
+
+        let err = fetch_and_parse(&url, &options(1)).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Transport);
+        assert!(err.report().contains("(2 attempts)"));
+    }
+
+    #[test]
+    fn should_not_retry_invalid_urls() {
+        let options = FetchOptions { backoff: Duration::from_secs(60), ..options(3) };
+
+        let err = fetch_and_parse("not a url", &options).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Transport);
+        assert!(err.report().contains("(1 attempt)"));
+    }
+
+    #[tokio::test]
+    async fn should_fetch_asynchronously_with_retries() {
+        let server = serve(vec![reply(429, ""), reply(200, " -15 \n")]);
+
+        assert_eq!(fetch_and_parse_async(&server.url, &options(2)).await.unwrap(), -15);
+        assert_eq!(server.hits.load(Ordering::SeqCst), 2);
+    }
+}
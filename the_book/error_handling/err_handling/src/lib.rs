@@ -1,43 +1,34 @@
-use std::{error::Error, fmt, fs::File, io::Read};
+use std::{fs::File, io::Read};
 
+pub mod combinators;
+pub mod diagnostic;
+pub mod error;
+pub mod http;
+pub mod number;
+pub mod reader;
 
-#[derive(Debug, PartialEq)]
-pub enum ParseError {
-    InvalidFormat,
-    EmptyString
-}
-
-impl fmt::Display for ParseError {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            ParseError::InvalidFormat => write!(f, "Invalid number format"),
-            ParseError::EmptyString => write!(f, "Empty input string"),
-        }
-    }
-}
+pub use error::{Context, Error, ErrorKind};
+pub use http::{fetch_and_parse, fetch_and_parse_async, FetchOptions};
+pub use number::{parse_number, Number, ParseError};
+pub use reader::{read_file_and_parse_all, Mode};
 
-impl Error for ParseError {}
 
-pub fn parse_number(s: &str) -> Result<i32, ParseError> {
-
-    if s.is_empty() {
-        return Err(ParseError::EmptyString)
-    }
+pub fn read_file_and_parse(path: &str) -> Result<i32, Error> {
+    let mut file = File::open(path).with_context(|| format!("while opening {}", path))?;
+    let mut contents = String::new();
+    file.read_to_string(&mut contents).with_context(|| format!("while reading {}", path))?;
 
-    s.parse().map_err(|_| ParseError::InvalidFormat)
+    parse_first_line(&contents).map_err(|e| e.in_file(path))
 }
 
-pub fn read_file_and_parse(path: &str) -> Result<i32, Box<dyn Error>> {
-    let mut file = File::open(path)?;  // Use the provided path
-    let mut contents = String::new();
-    file.read_to_string(&mut contents)?;  // Handle read error with ?
-
+// Shared by the file and HTTP readers, which both only look at the first line.
+fn parse_first_line(contents: &str) -> Result<i32, Error> {
     match contents.lines().next() {
         Some(line) => {
-            let num = parse_number(line)?;  // Propagate ParseError
+            let num = parse_number(line).map_err(|e| Error::parse(line, 1, e))?;
             Ok(num)
         },
-        None => Err("File is empty or has no valid lines".into()),  // Convert &str to Box<dyn Error>
+        None => Err(Error::EmptyInput),
     }
 }
 
@@ -49,14 +40,15 @@ mod tests {
     fn should_return_ok() {
         let s = "30";
 
-        assert_eq!(parse_number(s).unwrap(), 30);
+        assert_eq!(parse_number::<i32>(s).unwrap(), 30);
     }
 
     #[test]
+    #[allow(clippy::single_match)]
     fn should_fail_due_to_empty_string() {
         let s = "";
 
-        match parse_number(s) {
+        match parse_number::<i32>(s) {
             Err(e) => {
                 assert_eq!(e, ParseError::EmptyString)
             },
@@ -65,12 +57,37 @@ mod tests {
     }
 
     #[test]
+    fn should_report_missing_file_with_context() {
+        let err = read_file_and_parse("does_not_exist.txt").unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Io);
+        assert!(err.report().starts_with("error: while opening does_not_exist.txt\ncaused by: I/O error"));
+    }
+
+    #[test]
+    fn should_report_parse_error_with_line() {
+        let err = read_file_and_parse("hello_world.txt").unwrap_err();
+
+        assert_eq!(err.to_string(), "in hello_world.txt");
+        match err.root() {
+            Error::Parse { text, line, column, source } => {
+                assert_eq!(text, "Hello World");
+                assert_eq!(*line, 1);
+                assert_eq!(*column, 0);
+                assert_eq!(*source, ParseError::InvalidDigit { position: 0, found: 'H' });
+            },
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    #[allow(clippy::single_match)]
     fn should_fail_due_to_invalid_format() {
         let s = "hello world";
 
-        match parse_number(s) {
+        match parse_number::<i32>(s) {
             Err(e) => {
-                assert_eq!(e, ParseError::InvalidFormat)
+                assert_eq!(e, ParseError::InvalidDigit { position: 0, found: 'h' })
             },
             _ => ()
         }
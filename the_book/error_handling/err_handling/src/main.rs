@@ -1,2 +1,15 @@
+use std::io::IsTerminal;
+
+use err_handling::{
+    diagnostic::{render_error, Style},
+    read_file_and_parse,
+};
+
 fn main() {
+    let style = if std::io::stderr().is_terminal() { Style::Ansi } else { Style::Plain };
+
+    match read_file_and_parse("hello_world.txt") {
+        Ok(num) => println!("Parsed {}", num),
+        Err(e) => eprint!("{}", render_error(&e, style)),
+    }
 }
@@ -0,0 +1,280 @@
+use std::{error::Error as StdError, fmt};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ParseError {
+    InvalidFormat,
+    EmptyString,
+    /// `position` is the character index of `found` in the original input.
+    InvalidDigit { position: usize, found: char },
+    /// The value does not fit in the requested type.
+    Overflow,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::InvalidFormat => write!(f, "Invalid number format"),
+            ParseError::EmptyString => write!(f, "Empty input string"),
+            ParseError::InvalidDigit { position, found } => {
+                write!(f, "Invalid digit '{}' at position {}", found, position)
+            },
+            ParseError::Overflow => write!(f, "Number out of range for target type"),
+        }
+    }
+}
+
+impl StdError for ParseError {}
+
+/// A numeric type that [`parse_number`] can produce.
+///
+/// Implemented for every primitive integer type, `f32` and `f64`.
+pub trait Number: Sized {
+    /// Parses `s`, which has no surrounding whitespace and starts at character
+    /// `offset` of the original input. An empty `s` is
+    /// [`ParseError::EmptyString`].
+    fn parse_trimmed(s: &str, offset: usize) -> Result<Self, ParseError>;
+}
+
+/// Parses a number, ignoring leading and trailing whitespace.
+///
+/// Integers accept an optional sign, a `0x`, `0o` or `0b` radix prefix and
+/// `_` between digits or straight after the prefix, e.g. `-0x_ff` or
+/// `1_000_000`. Floats accept the usual decimal and exponent forms, plus `_`
+/// between digits, so not next to `.`, `e` or `E`.
+pub fn parse_number<T: Number>(s: &str) -> Result<T, ParseError> {
+    let trimmed = s.trim();
+
+    if trimmed.is_empty() {
+        return Err(ParseError::EmptyString);
+    }
+
+    let leading = s.len() - s.trim_start().len();
+    T::parse_trimmed(trimmed, s[..leading].chars().count())
+}
+
+// Splits off an optional sign and reports whether it was `-`.
+fn split_sign(s: &str) -> (bool, &str) {
+    if let Some(rest) = s.strip_prefix('-') {
+        (true, rest)
+    } else {
+        (false, s.strip_prefix('+').unwrap_or(s))
+    }
+}
+
+fn split_radix(s: &str) -> (u32, &str) {
+    match s.get(..2) {
+        Some("0x") | Some("0X") => (16, &s[2..]),
+        Some("0o") | Some("0O") => (8, &s[2..]),
+        Some("0b") | Some("0B") => (2, &s[2..]),
+        _ => (10, s),
+    }
+}
+
+// Checks that every character is a digit, one of the `symbols` or a `_`
+// with digits on both sides. Right after a radix prefix, which `prefixed`
+// says there was, a `_` may also come first. `offset` is the character index
+// of `digits` in the original input.
+fn check_digits(
+    digits: &str,
+    offset: usize,
+    prefixed: bool,
+    is_digit: impl Fn(char) -> bool,
+    is_symbol: impl Fn(char) -> bool,
+) -> Result<(), ParseError> {
+    let chars: Vec<char> = digits.chars().collect();
+    // Runs of `_` count as one separator.
+    let separates = |c: Option<&char>| c.is_some_and(|&c| c == '_' || is_digit(c));
+
+    for (index, &c) in chars.iter().enumerate() {
+        let valid = if c == '_' {
+            let before = index.checked_sub(1).and_then(|i| chars.get(i));
+            (separates(before) || (index == 0 && prefixed)) && separates(chars.get(index + 1))
+        } else {
+            is_digit(c) || is_symbol(c)
+        };
+
+        if !valid {
+            return Err(ParseError::InvalidDigit { position: offset + index, found: c });
+        }
+    }
+
+    Ok(())
+}
+
+fn parse_integer<T>(s: &str, offset: usize, signed: bool) -> Result<T, ParseError>
+where
+    T: TryFrom<u128> + TryFrom<i128>,
+{
+    if s.is_empty() {
+        return Err(ParseError::EmptyString);
+    }
+
+    let (negative, unsigned) = split_sign(s);
+    let sign_len = s.len() - unsigned.len();
+
+    if negative && !signed {
+        return Err(ParseError::InvalidDigit { position: offset, found: '-' });
+    }
+
+    let (radix, digits) = split_radix(unsigned);
+    let digits_offset = offset + sign_len + (unsigned.len() - digits.len());
+
+    if digits.is_empty() {
+        return Err(ParseError::InvalidFormat);
+    }
+
+    check_digits(digits, digits_offset, digits.len() < unsigned.len(), |c| c.is_digit(radix), |_| false)?;
+
+    let mut magnitude: u128 = 0;
+    for digit in digits.chars().filter_map(|c| c.to_digit(radix)) {
+        magnitude = magnitude
+            .checked_mul(radix as u128)
+            .and_then(|m| m.checked_add(digit as u128))
+            .ok_or(ParseError::Overflow)?;
+    }
+
+    if !negative {
+        return T::try_from(magnitude).map_err(|_| ParseError::Overflow);
+    }
+
+    let value = if magnitude == i128::MIN.unsigned_abs() {
+        i128::MIN
+    } else {
+        let magnitude = i128::try_from(magnitude).map_err(|_| ParseError::Overflow)?;
+        -magnitude
+    };
+
+    T::try_from(value).map_err(|_| ParseError::Overflow)
+}
+
+macro_rules! impl_integer {
+    ($signed:expr => $($t:ty),*) => {
+        $(
+            impl Number for $t {
+                fn parse_trimmed(s: &str, offset: usize) -> Result<Self, ParseError> {
+                    parse_integer(s, offset, $signed)
+                }
+            }
+        )*
+    };
+}
+
+impl_integer!(true => i8, i16, i32, i64, i128, isize);
+impl_integer!(false => u8, u16, u32, u64, u128, usize);
+
+fn parse_float<T>(s: &str, offset: usize) -> Result<T, ParseError>
+where
+    T: std::str::FromStr + Into<f64> + Copy,
+{
+    if s.is_empty() {
+        return Err(ParseError::EmptyString);
+    }
+
+    let (_, unsigned) = split_sign(s);
+    let sign_len = s.len() - unsigned.len();
+
+    let special = ["inf", "infinity", "nan"].iter().any(|w| unsigned.eq_ignore_ascii_case(w));
+
+    if !special {
+        check_digits(unsigned, offset + sign_len, false, |c| c.is_ascii_digit(), |c| {
+            matches!(c, '.' | 'e' | 'E' | '+' | '-')
+        })?;
+    }
+
+    let cleaned: String = s.chars().filter(|&c| c != '_').collect();
+    let value: T = cleaned.parse().map_err(|_| ParseError::InvalidFormat)?;
+
+    if value.into().is_infinite() && !special {
+        return Err(ParseError::Overflow);
+    }
+
+    Ok(value)
+}
+
+impl Number for f32 {
+    fn parse_trimmed(s: &str, offset: usize) -> Result<Self, ParseError> {
+        parse_float(s, offset)
+    }
+}
+
+impl Number for f64 {
+    fn parse_trimmed(s: &str, offset: usize) -> Result<Self, ParseError> {
+        parse_float(s, offset)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_parse_with_radix_prefixes() {
+        assert_eq!(parse_number::<i32>("0xff"), Ok(255));
+        assert_eq!(parse_number::<i32>("-0o17"), Ok(-15));
+        assert_eq!(parse_number::<u8>("0b1010_1010"), Ok(0b1010_1010));
+        assert_eq!(parse_number::<u64>("+0XDEAD_BEEF"), Ok(0xdead_beef));
+        assert_eq!(parse_number::<i32>("-0x_ff"), Ok(-255));
+        assert_eq!(parse_number::<i32>("0x_"), Err(ParseError::InvalidDigit { position: 2, found: '_' }));
+        assert_eq!(parse_number::<i32>("_1"), Err(ParseError::InvalidDigit { position: 0, found: '_' }));
+    }
+
+    #[test]
+    fn should_ignore_surrounding_whitespace_and_separators() {
+        assert_eq!(parse_number::<i64>("  1_000_000 \n"), Ok(1_000_000));
+        assert_eq!(parse_number::<f64>("\t-1_234.5e-1 "), Ok(-123.45));
+    }
+
+    #[test]
+    fn should_only_accept_float_separators_between_digits() {
+        assert_eq!(parse_number::<f64>("1_0.2_5e1_0"), Ok(10.25e10));
+        assert_eq!(parse_number::<f64>("1_.5"), Err(ParseError::InvalidDigit { position: 1, found: '_' }));
+        assert_eq!(parse_number::<f64>("1._5"), Err(ParseError::InvalidDigit { position: 2, found: '_' }));
+        assert_eq!(parse_number::<f64>("1e_5"), Err(ParseError::InvalidDigit { position: 2, found: '_' }));
+        assert_eq!(parse_number::<f64>("-_1.0"), Err(ParseError::InvalidDigit { position: 1, found: '_' }));
+    }
+
+    #[test]
+    fn should_report_invalid_digit_position() {
+        assert_eq!(
+            parse_number::<i32>("  12a4"),
+            Err(ParseError::InvalidDigit { position: 4, found: 'a' })
+        );
+        assert_eq!(
+            parse_number::<i32>("0b102"),
+            Err(ParseError::InvalidDigit { position: 4, found: '2' })
+        );
+        assert_eq!(
+            parse_number::<i32>("1__"),
+            Err(ParseError::InvalidDigit { position: 2, found: '_' })
+        );
+        assert_eq!(
+            parse_number::<u32>("-1"),
+            Err(ParseError::InvalidDigit { position: 0, found: '-' })
+        );
+        assert_eq!(
+            parse_number::<f32>("1.5x"),
+            Err(ParseError::InvalidDigit { position: 3, found: 'x' })
+        );
+    }
+
+    #[test]
+    fn should_report_overflow() {
+        assert_eq!(parse_number::<u8>("256"), Err(ParseError::Overflow));
+        assert_eq!(parse_number::<i8>("-129"), Err(ParseError::Overflow));
+        assert_eq!(parse_number::<i8>("-128"), Ok(i8::MIN));
+        assert_eq!(parse_number::<i128>(&i128::MIN.to_string()), Ok(i128::MIN));
+        assert_eq!(parse_number::<u128>(&format!("{}0", u128::MAX)), Err(ParseError::Overflow));
+        assert_eq!(parse_number::<f32>("1e39"), Err(ParseError::Overflow));
+    }
+
+    #[test]
+    fn should_distinguish_empty_and_malformed_input() {
+        assert_eq!(parse_number::<i32>("   "), Err(ParseError::EmptyString));
+        assert_eq!(i32::parse_trimmed("", 0), Err(ParseError::EmptyString));
+        assert_eq!(f64::parse_trimmed("", 0), Err(ParseError::EmptyString));
+        assert_eq!(parse_number::<i32>("0x"), Err(ParseError::InvalidFormat));
+        assert_eq!(parse_number::<i32>("-"), Err(ParseError::InvalidFormat));
+        assert_eq!(parse_number::<f64>("1.2.3"), Err(ParseError::InvalidFormat));
+        assert_eq!(parse_number::<f64>("-inf"), Ok(f64::NEG_INFINITY));
+    }
+}
@@ -0,0 +1,200 @@
+use std::{
+    fs::File,
+    io::{BufRead, BufReader, Lines},
+    marker::PhantomData,
+    path::Path,
+};
+
+use crate::{parse_number, Context, Error, ErrorKind, Number};
+
+/// How [`parse_all`] reacts to a bad line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mode {
+    /// Stop at the first error and return it.
+    FailFast,
+    /// Keep going and return every error as [`Error::Multiple`].
+    CollectAll,
+}
+
+/// Streams numbers out of a reader one line at a time.
+///
+/// Blank lines are skipped and everything after a `#` is treated as a comment.
+/// Only the current line is held in memory, so this works on inputs of any
+/// size as long as the caller does not collect the values.
+pub struct Numbers<R, T = i32> {
+    lines: Lines<R>,
+    line: usize,
+    number: PhantomData<T>,
+}
+
+impl<R: BufRead, T: Number> Numbers<R, T> {
+    pub fn new(reader: R) -> Numbers<R, T> {
+        Numbers { lines: reader.lines(), line: 0, number: PhantomData }
+    }
+}
+
+impl<R: BufRead, T: Number> Iterator for Numbers<R, T> {
+    type Item = Result<T, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let text = self.lines.next()?;
+            self.line += 1;
+
+            let text = match text {
+                Ok(text) => text,
+                Err(e) => return Some(Err(Error::from(e).context(format!("while reading line {}", self.line)))),
+            };
+
+            // Parse the line with the comment cut off but indentation intact, so
+            // positions in the error are columns in the file.
+            let value = match text.find('#') {
+                Some(start) => &text[..start],
+                None => &text,
+            };
+
+            if value.trim().is_empty() {
+                continue;
+            }
+
+            return Some(parse_number(value).map_err(|e| Error::parse(value, self.line, e)));
+        }
+    }
+}
+
+/// Opens `path` and streams its numbers with [`Numbers`].
+pub fn numbers_in_file<T: Number, P: AsRef<Path>>(path: P) -> Result<Numbers<BufReader<File>, T>, Error> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("while opening {}", path.display()))?;
+
+    Ok(Numbers::new(BufReader::new(file)))
+}
+
+/// Parses every number in `reader`.
+///
+/// Input with no numbers at all is reported as [`Error::EmptyInput`].
+pub fn parse_all<T: Number, R: BufRead>(reader: R, mode: Mode) -> Result<Vec<T>, Error> {
+    let mut values = Vec::new();
+    let mut errors = Vec::new();
+
+    for result in Numbers::new(reader) {
+        match result {
+            Ok(value) => values.push(value),
+            Err(e) if mode == Mode::FailFast => return Err(e),
+            // An I/O error means later lines cannot be trusted either.
+            Err(e) if e.kind() == ErrorKind::Io => {
+                errors.push(e);
+                break;
+            },
+            Err(e) => errors.push(e),
+        }
+    }
+
+    if !errors.is_empty() {
+        return Err(Error::Multiple(errors));
+    }
+
+    if values.is_empty() {
+        return Err(Error::EmptyInput);
+    }
+
+    Ok(values)
+}
+
+/// Like [`crate::read_file_and_parse`], but parses every line of the file.
+pub fn read_file_and_parse_all<T: Number, P: AsRef<Path>>(path: P, mode: Mode) -> Result<Vec<T>, Error> {
+    let path = path.as_ref();
+    let file = File::open(path).with_context(|| format!("while opening {}", path.display()))?;
+
+    parse_all(BufReader::new(file), mode).map_err(|e| e.in_file(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::{self, Read};
+
+    use super::*;
+    use crate::ParseError;
+
+    const INPUT: &str = "# totals\n10\n\n  20  # second\nabc\n30\n#40\nxyz\n";
+
+    #[test]
+    fn should_skip_blank_lines_and_comments() {
+        let values = parse_all::<i32, _>("1\n\n# skip me\n 2 \n3 # trailing\n".as_bytes(), Mode::FailFast).unwrap();
+
+        assert_eq!(values, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn should_stop_at_first_error_in_fail_fast_mode() {
+        let err = parse_all::<i32, _>(INPUT.as_bytes(), Mode::FailFast).unwrap_err();
+
+        assert!(matches!(err, Error::Parse { ref text, line: 5, .. } if text == "abc"));
+    }
+
+    #[test]
+    fn should_collect_every_error_in_collect_all_mode() {
+        let err = parse_all::<i32, _>(INPUT.as_bytes(), Mode::CollectAll).unwrap_err();
+
+        match err {
+            Error::Multiple(errors) => {
+                let lines: Vec<usize> = errors
+                    .iter()
+                    .map(|e| match e {
+                        Error::Parse { line, source, .. } => {
+                            assert!(matches!(source, ParseError::InvalidDigit { position: 0, .. }));
+                            *line
+                        },
+                        other => panic!("unexpected error: {:?}", other),
+                    })
+                    .collect();
+                assert_eq!(lines, vec![5, 8]);
+            },
+            other => panic!("unexpected error: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn should_report_input_without_numbers_as_empty() {
+        let err = parse_all::<i32, _>("\n# nothing here\n".as_bytes(), Mode::CollectAll).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::EmptyInput);
+    }
+
+    #[test]
+    fn should_attach_path_to_file_errors() {
+        let err = read_file_and_parse_all::<i32, _>("hello_world.txt", Mode::FailFast).unwrap_err();
+
+        assert_eq!(err.kind(), ErrorKind::Parse);
+        assert_eq!(err.to_string(), "in hello_world.txt");
+    }
+
+    // Produces "0\n1\n2\n..." on demand so the whole input never exists at once.
+    struct Generated {
+        next: u32,
+        end: u32,
+        pending: Vec<u8>,
+    }
+
+    impl Read for Generated {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            if self.pending.is_empty() && self.next < self.end {
+                self.pending = format!("{}\n", self.next % 10).into_bytes();
+                self.next += 1;
+            }
+            let n = buf.len().min(self.pending.len());
+            buf[..n].copy_from_slice(&self.pending[..n]);
+            self.pending.drain(..n);
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn should_stream_large_inputs() {
+        let reader = io::BufReader::new(Generated { next: 0, end: 500_000, pending: Vec::new() });
+
+        let sum: i64 = Numbers::<_, i64>::new(reader).map(|n| n.unwrap()).sum();
+
+        assert_eq!(sum, 45 * 50_000);
+    }
+}
@@ -1,89 +1,129 @@
 
 
 pub mod generics {
-    pub struct Pair<T: Copy> {
-        first: T, 
-        second: T,
+    use std::fmt::Display;
+    use std::mem;
+
+    /// Two values held together. The second type defaults to the first, so
+    /// `Pair<T>` is a pair of two `T`s.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+    pub struct Pair<A, B = A> {
+        first: A,
+        second: B,
     }
-    
-    impl<T: Copy> Pair<T> {
-        pub fn new(first: T, second: T) -> Pair<T> {
+
+    impl<A, B> Pair<A, B> {
+        pub fn new(first: A, second: B) -> Pair<A, B> {
             Pair {
                 first,
                 second,
             }
         }
-    
-        pub fn first(&self) -> T {
+
+        pub fn first(&self) -> &A {
+            &self.first
+        }
+
+        pub fn second(&self) -> &B {
+            &self.second
+        }
+
+        pub fn first_mut(&mut self) -> &mut A {
+            &mut self.first
+        }
+
+        pub fn second_mut(&mut self) -> &mut B {
+            &mut self.second
+        }
+
+        pub fn into_first(self) -> A {
             self.first
         }
-    
-        pub fn second(&self) -> T {
+
+        pub fn into_second(self) -> B {
             self.second
         }
-    
-        pub fn swap(&mut self) {
-            let temp = self.first;
-            self.first = self.second;
-            self.second = temp;
+
+        pub fn into_tuple(self) -> (A, B) {
+            (self.first, self.second)
         }
-    }
-       
-}
 
-pub mod trait_example {
-    use std::f64::consts::PI;
-    pub trait Area {
-        fn area(&self) -> f64;
-    }
-    
-    pub struct Circle {
-        pub radius: f64,
-    }
-    
-    impl Area for Circle {
-        fn area(&self) -> f64 {
-            PI * self.radius.powi(2)
+        pub fn as_ref(&self) -> Pair<&A, &B> {
+            Pair::new(&self.first, &self.second)
         }
-    }
-    
-    pub struct Square {
-        side: f64,
-    }
-    
-    impl Area for Square {
-        fn area(&self) -> f64 {
-            self.side.powi(2)
+
+        /// Swaps the values, and therefore the types, of a mixed pair.
+        pub fn swapped(self) -> Pair<B, A> {
+            Pair::new(self.second, self.first)
+        }
+
+        pub fn map_first<C, F: FnOnce(A) -> C>(self, f: F) -> Pair<C, B> {
+            Pair::new(f(self.first), self.second)
+        }
+
+        pub fn map_second<C, F: FnOnce(B) -> C>(self, f: F) -> Pair<A, C> {
+            Pair::new(self.first, f(self.second))
+        }
+
+        /// Combines two pairs position by position.
+        pub fn zip<C, D>(self, other: Pair<C, D>) -> Pair<(A, C), (B, D)> {
+            Pair::new((self.first, other.first), (self.second, other.second))
         }
     }
-    
-    pub struct Rectangle {
-        width: f64,
-        length: f64,
+
+    impl<T> Pair<T> {
+        pub fn swap(&mut self) {
+            mem::swap(&mut self.first, &mut self.second);
+        }
+
+        /// Applies `f` to both values.
+        pub fn map<U, F: FnMut(T) -> U>(self, mut f: F) -> Pair<U> {
+            Pair::new(f(self.first), f(self.second))
+        }
     }
-    
-    impl Area for Rectangle {
-        fn area(&self) -> f64 {
-            self.length * self.width
+
+    impl<A, B, C, D> Pair<(A, C), (B, D)> {
+        /// The inverse of [`Pair::zip`].
+        pub fn unzip(self) -> (Pair<A, B>, Pair<C, D>) {
+            let (a, c) = self.first;
+            let (b, d) = self.second;
+
+            (Pair::new(a, b), Pair::new(c, d))
         }
     }
-    
-    pub fn print_area<T: Area>(shape: T) {
-        println!("Area of shape: {}", shape.area())
+
+    impl<T: PartialOrd + Display> Pair<T> {
+        /// The larger value, or the first one when they are equal or unordered.
+        pub fn largest(&self) -> &T {
+            if self.second > self.first {
+                &self.second
+            } else {
+                &self.first
+            }
+        }
+
+        pub fn cmp_display(&self) -> String {
+            format!("The largest member is {}", self.largest())
+        }
     }
-    
-}
 
-pub mod lifetimes_example {
-    pub fn longest_string<'a>(s1: &'a str, s2: &'a str) -> &'a str {
-        if s1.len() > s2.len() {
-            return s1;
+    impl<A, B> From<(A, B)> for Pair<A, B> {
+        fn from((first, second): (A, B)) -> Pair<A, B> {
+            Pair::new(first, second)
         }
+    }
 
-        s2
+    impl<A, B> From<Pair<A, B>> for (A, B) {
+        fn from(pair: Pair<A, B>) -> (A, B) {
+            pair.into_tuple()
+        }
     }
 }
 
+pub mod trait_example;
+
+pub mod lifetimes_example;
+
 #[cfg(test)]
 mod tests {
     use std::f64::consts::PI;
@@ -99,8 +139,8 @@ mod tests {
 
         let pair = generics::Pair::new(first, second);
 
-        assert_eq!(pair.first(), first);
-        assert_eq!(pair.second(), second);
+        assert_eq!(*pair.first(), first);
+        assert_eq!(*pair.second(), second);
     }
 
     #[test]
@@ -112,11 +152,66 @@ mod tests {
 
         pair.swap();
 
-        assert_eq!(pair.first(), second);
-        assert_eq!(pair.second(), first);
+        assert_eq!(*pair.first(), second);
+        assert_eq!(*pair.second(), first);
+    }
+
+    #[test]
+    fn verify_pair_works_with_non_copy_types() {
+        let mut pair = generics::Pair::new(String::from("left"), String::from("right"));
+
+        pair.swap();
+        pair.first_mut().push('!');
+
+        assert_eq!(pair.first(), "right!");
+        assert_eq!(pair.as_ref().into_second(), "left");
+        assert_eq!(pair.into_first(), "right!");
+    }
+
+    #[test]
+    fn verify_mixed_type_pair() {
+        let pair: generics::Pair<&str, u32> = generics::Pair::new("age", 26);
+
+        let swapped = pair.swapped();
+        let mapped = pair.map_first(str::len).map_second(|age| age + 1);
+
+        assert_eq!(swapped.into_tuple(), (26, "age"));
+        assert_eq!(mapped.into_tuple(), (3, 27));
+    }
+
+    #[test]
+    fn verify_map_zip_and_unzip() {
+        let numbers = generics::Pair::new(1, 2).map(|n| n * 10);
+        let names = generics::Pair::new("one", "two");
+
+        let zipped = numbers.zip(names);
+        assert_eq!(zipped.into_tuple(), ((10, "one"), (20, "two")));
+
+        let (numbers, names) = zipped.unzip();
+        assert_eq!(numbers, generics::Pair::new(10, 20));
+        assert_eq!(names, generics::Pair::new("one", "two"));
+    }
+
+    #[test]
+    fn verify_largest_and_cmp_display() {
+        let pair = generics::Pair::new(3.5, 7.25);
+        let tie = generics::Pair::new(String::from("b"), String::from("b"));
+
+        assert_eq!(*pair.largest(), 7.25);
+        assert_eq!(pair.cmp_display(), "The largest member is 7.25");
+        assert!(std::ptr::eq(tie.largest(), tie.first()));
+    }
+
+    #[test]
+    fn verify_tuple_conversions() {
+        let pair: generics::Pair<char, bool> = ('x', true).into();
+        let tuple: (char, bool) = pair.into();
+
+        assert_eq!(tuple, ('x', true));
     }
 
     #[test]
+    #[allow(clippy::useless_conversion)]
     fn verify_area() {
         let radius :f64 = 12.00;
         let area: f64 = (PI * radius * radius).into();
@@ -0,0 +1,183 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+pub mod tokenizer;
+
+pub fn longest_string<'a>(s1: &'a str, s2: &'a str) -> &'a str {
+    if s1.len() > s2.len() {
+        return s1;
+    }
+
+    s2
+}
+
+/// What "length" means when comparing strings.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Measure {
+    /// UTF-8 bytes, as `str::len`.
+    #[default]
+    Bytes,
+    /// Unicode scalar values, so "é" written as `e` plus a combining accent
+    /// counts as two.
+    Chars,
+    /// Extended grapheme clusters: what a reader would call characters.
+    Graphemes,
+    /// Columns taken up in a terminal, where most CJK characters and emoji
+    /// are two wide.
+    Width,
+}
+
+impl Measure {
+    pub fn len(self, s: &str) -> usize {
+        match self {
+            Measure::Bytes => s.len(),
+            Measure::Chars => s.chars().count(),
+            Measure::Graphemes => s.graphemes(true).count(),
+            Measure::Width => s.width(),
+        }
+    }
+}
+
+/// Which string wins when two measure the same.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Tie {
+    #[default]
+    First,
+    Last,
+}
+
+/// Like [`longest_string`], but measured by `measure` and with ties settled
+/// by `tie` rather than always going to `s2`.
+pub fn longest_by<'a>(s1: &'a str, s2: &'a str, measure: Measure, tie: Tie) -> &'a str {
+    let (len1, len2) = (measure.len(s1), measure.len(s2));
+
+    if len1 > len2 || (len1 == len2 && tie == Tie::First) {
+        s1
+    } else {
+        s2
+    }
+}
+
+// Picks the string whose length is best according to `better`, which is
+// asked whether a challenger beats the current winner.
+fn pick<'a, I, F>(strings: I, measure: Measure, tie: Tie, better: F) -> Option<&'a str>
+where
+    I: IntoIterator<Item = &'a str>,
+    F: Fn(usize, usize) -> bool,
+{
+    let mut best: Option<(&'a str, usize)> = None;
+
+    for s in strings {
+        let len = measure.len(s);
+        match best {
+            Some((_, best_len)) if !(better(len, best_len) || (len == best_len && tie == Tie::Last)) => {}
+            _ => best = Some((s, len)),
+        }
+    }
+
+    best.map(|(s, _)| s)
+}
+
+/// The longest of any number of strings, or `None` if there are none.
+///
+/// The result borrows from the input strings themselves, not from the
+/// iterator, so it outlives a temporary `Vec` of slices.
+pub fn longest<'a, I: IntoIterator<Item = &'a str>>(strings: I, measure: Measure, tie: Tie) -> Option<&'a str> {
+    pick(strings, measure, tie, |len, best| len > best)
+}
+
+pub fn shortest<'a, I: IntoIterator<Item = &'a str>>(strings: I, measure: Measure, tie: Tie) -> Option<&'a str> {
+    pick(strings, measure, tie, |len, best| len < best)
+}
+
+/// The longest prefix shared by every string, borrowed from the first one.
+///
+/// Strings are compared a grapheme cluster at a time, so the prefix never
+/// ends halfway through an accented letter or emoji sequence. No strings
+/// share the empty prefix.
+pub fn longest_common_prefix<'a, I: IntoIterator<Item = &'a str>>(strings: I) -> &'a str {
+    let mut strings = strings.into_iter();
+    let Some(mut prefix) = strings.next() else {
+        return "";
+    };
+
+    for s in strings {
+        let shared: usize = prefix
+            .graphemes(true)
+            .zip(s.graphemes(true))
+            .take_while(|(a, b)| a == b)
+            .map(|(a, _)| a.len())
+            .sum();
+        prefix = &prefix[..shared];
+
+        if prefix.is_empty() {
+            break;
+        }
+    }
+
+    prefix
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "é" as `e` followed by U+0301 COMBINING ACUTE ACCENT.
+    const CAFE_COMBINING: &str = "cafe\u{301}";
+
+    #[test]
+    fn should_measure_in_each_unit() {
+        let lengths = |s| [Measure::Bytes, Measure::Chars, Measure::Graphemes, Measure::Width].map(|m| m.len(s));
+
+        assert_eq!(lengths("hello"), [5, 5, 5, 5]);
+        assert_eq!(lengths(CAFE_COMBINING), [6, 5, 4, 4]);
+        assert_eq!(lengths("日本"), [6, 2, 2, 4]);
+        assert_eq!(lengths("👨‍👩‍👧"), [18, 5, 1, 2]);
+    }
+
+    #[test]
+    fn should_pick_longest_by_measure() {
+        let cafe = CAFE_COMBINING;
+        let name = "Zoë!";
+
+        assert_eq!(longest_by(cafe, name, Measure::Bytes, Tie::First), cafe);
+        assert_eq!(longest_by(cafe, name, Measure::Chars, Tie::First), cafe);
+        assert_eq!(longest_by(cafe, name, Measure::Graphemes, Tie::First), cafe);
+        assert_eq!(longest_by(cafe, name, Measure::Graphemes, Tie::Last), name);
+        assert_eq!(longest_by("日本", "abc", Measure::Width, Tie::First), "日本");
+        assert_eq!(longest_by("日本", "abc", Measure::Chars, Tie::First), "abc");
+    }
+
+    #[test]
+    fn should_settle_ties_by_policy() {
+        let words = ["one", "two", "six", "ab"];
+
+        assert_eq!(longest(words, Measure::Bytes, Tie::First), Some("one"));
+        assert_eq!(longest(words, Measure::Bytes, Tie::Last), Some("six"));
+        assert_eq!(shortest(["to", "be", "or"], Measure::Chars, Tie::First), Some("to"));
+        assert_eq!(shortest(["to", "be", "or"], Measure::Chars, Tie::Last), Some("or"));
+        assert_eq!(longest([], Measure::Bytes, Tie::First), None);
+    }
+
+    #[test]
+    fn should_outlive_the_iterator() {
+        let text = String::from("the quick brown fox jumps");
+
+        let word = {
+            let words: Vec<&str> = text.split(' ').collect();
+            longest(words, Measure::Graphemes, Tie::First)
+        };
+
+        assert_eq!(word, Some("quick"));
+    }
+
+    #[test]
+    fn should_find_common_prefix_on_grapheme_boundaries() {
+        assert_eq!(longest_common_prefix(["interstellar", "internet", "interval"]), "inter");
+        assert_eq!(longest_common_prefix(["cafe", CAFE_COMBINING]), "caf");
+        assert_eq!(longest_common_prefix(["👨‍👩‍👧 family", "👨‍👩‍👦 family"]), "");
+        assert_eq!(longest_common_prefix(["alone"]), "alone");
+        assert_eq!(longest_common_prefix(["abc", "xyz", "abd"]), "");
+        assert_eq!(longest_common_prefix(Vec::<&str>::new()), "");
+    }
+}
@@ -0,0 +1,411 @@
+//! A tokenizer that never copies its input.
+//!
+//! Every [`Token`] borrows its text straight out of the source string, so
+//! tokens carry the source's lifetime `'a` rather than the tokenizer's. They
+//! stay valid after the tokenizer or cursor that produced them is dropped.
+
+use std::error::Error;
+use std::fmt;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// A letter or `_`, then any letters, digits or `_`.
+    Word,
+    /// Digits with at most one `.` between them, e.g. `42` or `3.14`.
+    Number,
+    /// A single character that is none of the others, e.g. `+` or `,`.
+    Punctuation,
+    /// A run of whitespace, including new lines.
+    Whitespace,
+}
+
+/// Byte offsets into the source, `end` exclusive, so `&source[start..end]`
+/// is the token's text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    pub span: Span,
+}
+
+/// Splits a string into tokens. Every byte of the source ends up in exactly
+/// one token.
+#[derive(Debug, Clone)]
+pub struct Tokenizer<'a> {
+    source: &'a str,
+    position: usize,
+}
+
+fn is_word_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn is_word_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_'
+}
+
+impl<'a> Tokenizer<'a> {
+    pub fn new(source: &'a str) -> Tokenizer<'a> {
+        Tokenizer { source, position: 0 }
+    }
+
+    pub fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Byte offset of the next token.
+    pub fn offset(&self) -> usize {
+        self.position
+    }
+
+    // Length in bytes of the longest prefix of `rest` whose chars all match.
+    fn run(rest: &str, matches: impl Fn(char) -> bool) -> usize {
+        rest.find(|c| !matches(c)).unwrap_or(rest.len())
+    }
+
+    fn number_len(rest: &str) -> usize {
+        let digits = Tokenizer::run(rest, |c| c.is_ascii_digit());
+        let fraction = rest[digits..].strip_prefix('.').map_or(0, |after| Tokenizer::run(after, |c| c.is_ascii_digit()));
+
+        // A trailing dot, as in "3.", is punctuation rather than part of the number.
+        if fraction > 0 { digits + 1 + fraction } else { digits }
+    }
+}
+
+impl<'a> Iterator for Tokenizer<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let rest = &self.source[self.position..];
+        let first = rest.chars().next()?;
+
+        let (kind, len) = if first.is_whitespace() {
+            (TokenKind::Whitespace, Tokenizer::run(rest, char::is_whitespace))
+        } else if first.is_ascii_digit() {
+            (TokenKind::Number, Tokenizer::number_len(rest))
+        } else if is_word_start(first) {
+            (TokenKind::Word, Tokenizer::run(rest, is_word_char))
+        } else {
+            (TokenKind::Punctuation, first.len_utf8())
+        };
+
+        let span = Span { start: self.position, end: self.position + len };
+        self.position = span.end;
+
+        Some(Token { kind, text: &self.source[span.start..span.end], span })
+    }
+}
+
+/// What [`Cursor::expect`] wanted and what it found instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unexpected<'a> {
+    pub expected: TokenKind,
+    /// `None` at the end of the input.
+    pub found: Option<Token<'a>>,
+    pub offset: usize,
+}
+
+impl fmt::Display for Unexpected<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.found {
+            Some(token) => write!(
+                f,
+                "expected {:?} at byte {}, found {:?} '{}'",
+                self.expected, self.offset, token.kind, token.text
+            ),
+            None => write!(f, "expected {:?} at byte {}, found end of input", self.expected, self.offset),
+        }
+    }
+}
+
+impl Error for Unexpected<'_> {}
+
+/// A saved cursor position to [`Cursor::reset`] to.
+///
+/// It is only an offset, so nothing stops it being passed to a different
+/// cursor than the one it came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Checkpoint(usize);
+
+/// A position in a token stream that can look ahead and back up, for writing
+/// small recursive descent parsers.
+///
+/// The cursor is only a string slice and an offset, so peeking re-scans the
+/// next token instead of buffering it and backtracking is free.
+#[derive(Debug, Clone)]
+pub struct Cursor<'a> {
+    tokens: Tokenizer<'a>,
+    skip_whitespace: bool,
+}
+
+impl<'a> Cursor<'a> {
+    pub fn new(source: &'a str) -> Cursor<'a> {
+        Cursor { tokens: Tokenizer::new(source), skip_whitespace: false }
+    }
+
+    /// Steps over whitespace tokens as if they were not there.
+    pub fn skip_whitespace(mut self) -> Cursor<'a> {
+        self.skip_whitespace = true;
+        self
+    }
+
+    /// Byte offset of the next token.
+    pub fn offset(&self) -> usize {
+        let mut tokens = self.tokens.clone();
+        self.skip_ignored(&mut tokens);
+        tokens.offset()
+    }
+
+    /// The input not consumed yet.
+    pub fn rest(&self) -> &'a str {
+        &self.tokens.source()[self.offset()..]
+    }
+
+    pub fn is_at_end(&self) -> bool {
+        self.peek().is_none()
+    }
+
+    fn skip_ignored(&self, tokens: &mut Tokenizer<'a>) {
+        if self.skip_whitespace {
+            let rest = &tokens.source()[tokens.offset()..];
+            tokens.position += Tokenizer::run(rest, char::is_whitespace);
+        }
+    }
+
+    pub fn peek(&self) -> Option<Token<'a>> {
+        self.peek_nth(0)
+    }
+
+    /// The token `n` places ahead, where `peek_nth(0)` is the next one.
+    pub fn peek_nth(&self, n: usize) -> Option<Token<'a>> {
+        let mut tokens = self.tokens.clone();
+        for _ in 0..n {
+            self.skip_ignored(&mut tokens);
+            tokens.next()?;
+        }
+        self.skip_ignored(&mut tokens);
+        tokens.next()
+    }
+
+    /// Consumes the next token if it is of the given kind.
+    pub fn eat(&mut self, kind: TokenKind) -> Option<Token<'a>> {
+        self.next_if(|token| token.kind == kind)
+    }
+
+    /// Consumes the next token if its text is exactly `text`.
+    pub fn eat_text(&mut self, text: &str) -> Option<Token<'a>> {
+        self.next_if(|token| token.text == text)
+    }
+
+    pub fn next_if(&mut self, predicate: impl FnOnce(&Token<'a>) -> bool) -> Option<Token<'a>> {
+        self.peek().filter(predicate)?;
+        self.next()
+    }
+
+    pub fn expect(&mut self, kind: TokenKind) -> Result<Token<'a>, Unexpected<'a>> {
+        let offset = self.offset();
+        match self.eat(kind) {
+            Some(token) => Ok(token),
+            None => Err(Unexpected { expected: kind, found: self.peek(), offset }),
+        }
+    }
+
+    pub fn checkpoint(&self) -> Checkpoint {
+        Checkpoint(self.offset())
+    }
+
+    /// Moves back, or forward, to `checkpoint`.
+    ///
+    /// Panics if `checkpoint` is past the end of this cursor's source or not
+    /// on a character boundary in it, which can only happen with a
+    /// checkpoint taken from another cursor.
+    pub fn reset(&mut self, checkpoint: Checkpoint) {
+        assert!(
+            self.tokens.source().is_char_boundary(checkpoint.0),
+            "checkpoint at byte {} is not a position in this cursor's source",
+            checkpoint.0
+        );
+        self.tokens.position = checkpoint.0;
+    }
+
+    /// The source text consumed since `checkpoint`, without copying it.
+    ///
+    /// `None` if the cursor is now before `checkpoint`, e.g. after resetting
+    /// to an earlier one, or if `checkpoint` came from another cursor and
+    /// does not fit this one's source.
+    pub fn since(&self, checkpoint: Checkpoint) -> Option<&'a str> {
+        self.tokens.source().get(checkpoint.0..self.tokens.offset())
+    }
+
+    /// Runs `parse`, rewinding to where it started if it returns `None`.
+    pub fn attempt<T>(&mut self, parse: impl FnOnce(&mut Cursor<'a>) -> Option<T>) -> Option<T> {
+        let start = self.checkpoint();
+        let result = parse(self);
+        if result.is_none() {
+            self.reset(start);
+        }
+        result
+    }
+}
+
+impl<'a> Iterator for Cursor<'a> {
+    type Item = Token<'a>;
+
+    fn next(&mut self) -> Option<Token<'a>> {
+        let mut tokens = self.tokens.clone();
+        self.skip_ignored(&mut tokens);
+        self.tokens = tokens;
+        self.tokens.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use TokenKind::*;
+
+    fn kinds_and_texts(source: &str) -> Vec<(TokenKind, &str)> {
+        Tokenizer::new(source).map(|t| (t.kind, t.text)).collect()
+    }
+
+    #[test]
+    fn should_split_into_kinds() {
+        assert_eq!(
+            kinds_and_texts("let x_1 = 3.14+2.;\n"),
+            vec![
+                (Word, "let"),
+                (Whitespace, " "),
+                (Word, "x_1"),
+                (Whitespace, " "),
+                (Punctuation, "="),
+                (Whitespace, " "),
+                (Number, "3.14"),
+                (Punctuation, "+"),
+                (Number, "2"),
+                (Punctuation, "."),
+                (Punctuation, ";"),
+                (Whitespace, "\n"),
+            ]
+        );
+    }
+
+    #[test]
+    fn should_give_byte_spans_for_unicode() {
+        let source = "naïve → 日本";
+
+        let tokens: Vec<Token> = Tokenizer::new(source).collect();
+
+        assert_eq!(tokens.iter().map(|t| t.text).collect::<Vec<_>>(), vec!["naïve", " ", "→", " ", "日本"]);
+        assert_eq!(tokens[2].span, Span { start: 7, end: 10 });
+        assert!(tokens.iter().all(|t| &source[t.span.start..t.span.end] == t.text));
+    }
+
+    #[test]
+    fn should_borrow_from_source_not_tokenizer() {
+        let source = String::from("alpha beta");
+
+        let first = {
+            let mut tokenizer = Tokenizer::new(&source);
+            tokenizer.next().unwrap()
+        };
+
+        assert_eq!(first.text, "alpha");
+        assert_eq!(first.text.as_ptr(), source.as_ptr());
+    }
+
+    #[test]
+    fn should_peek_without_consuming() {
+        let mut cursor = Cursor::new("a + b").skip_whitespace();
+
+        assert_eq!(cursor.peek().map(|t| t.text), Some("a"));
+        assert_eq!(cursor.peek_nth(2).map(|t| t.text), Some("b"));
+        assert_eq!(cursor.peek_nth(3), None);
+        assert_eq!(cursor.next().map(|t| t.text), Some("a"));
+        assert_eq!(cursor.offset(), 2);
+        assert_eq!(cursor.rest(), "+ b");
+        assert!(cursor.eat(Word).is_none());
+        assert!(cursor.eat_text("+").is_some());
+        assert_eq!(cursor.collect::<Vec<_>>().len(), 1);
+    }
+
+    #[test]
+    fn should_report_unexpected_tokens() {
+        let mut cursor = Cursor::new("x = ").skip_whitespace();
+
+        assert!(cursor.expect(Word).is_ok());
+        let err = cursor.expect(Number).unwrap_err();
+        assert_eq!(err.to_string(), "expected Number at byte 2, found Punctuation '='");
+        cursor.next();
+        assert_eq!(cursor.expect(Word).unwrap_err().found, None);
+        assert!(cursor.is_at_end());
+    }
+
+    // date := number '-' number '-' number
+    fn date<'a>(cursor: &mut Cursor<'a>) -> Option<&'a str> {
+        cursor.attempt(|c| {
+            let start = c.checkpoint();
+            c.eat(Number)?;
+            c.eat_text("-")?;
+            c.eat(Number)?;
+            c.eat_text("-")?;
+            c.eat(Number)?;
+            c.since(start)
+        })
+    }
+
+    // expression := (date | number) (('+' | '-') (date | number))*
+    fn expression<'a>(cursor: &mut Cursor<'a>) -> Vec<&'a str> {
+        let mut terms = Vec::new();
+        while let Some(term) = date(cursor).or_else(|| cursor.eat(Number).map(|t| t.text)) {
+            terms.push(term);
+            if cursor.eat_text("+").or_else(|| cursor.eat_text("-")).is_none() {
+                break;
+            }
+        }
+        terms
+    }
+
+    #[test]
+    fn should_backtrack_in_a_small_parser() {
+        let mut cursor = Cursor::new(" 2024-01-31 + 7 + 2024-1").skip_whitespace();
+
+        assert_eq!(expression(&mut cursor), vec!["2024-01-31", "7", "2024", "1"]);
+        assert!(cursor.is_at_end());
+
+        // A failed attempt leaves the cursor where it was.
+        let mut cursor = Cursor::new("12-x");
+        assert_eq!(date(&mut cursor), None);
+        assert_eq!(cursor.offset(), 0);
+    }
+
+    #[test]
+    fn should_not_slice_before_or_outside_the_source() {
+        let mut cursor = Cursor::new("héllo world").skip_whitespace();
+        let start = cursor.checkpoint();
+        cursor.next();
+        let later = cursor.checkpoint();
+
+        assert_eq!(cursor.since(start), Some("héllo"));
+        cursor.reset(start);
+        assert_eq!(cursor.since(later), None);
+
+        let other = Cursor::new("hé");
+        assert_eq!(other.since(later), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "not a position in this cursor's source")]
+    fn should_refuse_checkpoints_from_another_cursor() {
+        let mut long = Cursor::new("hello");
+        long.next();
+        let past_the_end = long.checkpoint();
+
+        Cursor::new("hé").reset(past_the_end);
+    }
+}
@@ -0,0 +1,578 @@
+use std::error::Error;
+use std::f64::consts::PI;
+use std::fmt;
+
+pub mod description;
+pub mod geometry;
+pub mod svg;
+
+use geometry::{segments_intersect, Geometry, Outline, EPSILON};
+
+pub trait Area {
+    fn area(&self) -> f64;
+}
+
+pub trait Perimeter {
+    fn perimeter(&self) -> f64;
+}
+
+/// Any closed 2D shape, usable as `Box<dyn Shape>`.
+pub trait Shape: Area + Perimeter + Geometry {
+    fn name(&self) -> &'static str;
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ShapeError {
+    /// A length that is zero, negative, infinite or NaN.
+    InvalidDimension { name: &'static str, value: f64 },
+    /// Side lengths that break the triangle inequality.
+    InvalidTriangle { a: f64, b: f64, c: f64 },
+    TooFewSides(usize),
+    /// A polygon whose vertices enclose no area.
+    Degenerate,
+    /// A polygon with two edges that cross or touch away from a shared vertex.
+    SelfIntersecting,
+}
+
+impl fmt::Display for ShapeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ShapeError::InvalidDimension { name, value } => {
+                write!(f, "{} must be a positive finite number, got {}", name, value)
+            }
+            ShapeError::InvalidTriangle { a, b, c } => {
+                write!(f, "sides {}, {} and {} do not form a triangle", a, b, c)
+            }
+            ShapeError::TooFewSides(n) => write!(f, "a polygon needs at least 3 sides, got {}", n),
+            ShapeError::Degenerate => write!(f, "polygon has zero area"),
+            ShapeError::SelfIntersecting => write!(f, "polygon edges cross each other"),
+        }
+    }
+}
+
+impl Error for ShapeError {}
+
+fn check_dimension(name: &'static str, value: f64) -> Result<f64, ShapeError> {
+    if value.is_finite() && value > 0.0 {
+        Ok(value)
+    } else {
+        Err(ShapeError::InvalidDimension { name, value })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    pub fn new(x: f64, y: f64) -> Point {
+        Point { x, y }
+    }
+
+    pub fn distance(&self, other: &Point) -> f64 {
+        (self.x - other.x).hypot(self.y - other.y)
+    }
+
+    pub fn offset(&self, dx: f64, dy: f64) -> Point {
+        Point::new(self.x + dx, self.y + dy)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Circle {
+    pub radius: f64,
+}
+
+impl Circle {
+    pub fn new(radius: f64) -> Result<Circle, ShapeError> {
+        Ok(Circle { radius: check_dimension("radius", radius)? })
+    }
+}
+
+impl Area for Circle {
+    fn area(&self) -> f64 {
+        PI * self.radius.powi(2)
+    }
+}
+
+impl Perimeter for Circle {
+    fn perimeter(&self) -> f64 {
+        2.0 * PI * self.radius
+    }
+}
+
+impl Shape for Circle {
+    fn name(&self) -> &'static str {
+        "circle"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Square {
+    side: f64,
+}
+
+impl Square {
+    pub fn new(side: f64) -> Result<Square, ShapeError> {
+        Ok(Square { side: check_dimension("side", side)? })
+    }
+
+    pub fn side(&self) -> f64 {
+        self.side
+    }
+}
+
+impl Area for Square {
+    fn area(&self) -> f64 {
+        self.side.powi(2)
+    }
+}
+
+impl Perimeter for Square {
+    fn perimeter(&self) -> f64 {
+        4.0 * self.side
+    }
+}
+
+impl Shape for Square {
+    fn name(&self) -> &'static str {
+        "square"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Rectangle {
+    width: f64,
+    length: f64,
+}
+
+impl Rectangle {
+    pub fn new(width: f64, length: f64) -> Result<Rectangle, ShapeError> {
+        Ok(Rectangle {
+            width: check_dimension("width", width)?,
+            length: check_dimension("length", length)?,
+        })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.width
+    }
+
+    pub fn length(&self) -> f64 {
+        self.length
+    }
+}
+
+impl Area for Rectangle {
+    fn area(&self) -> f64 {
+        self.length * self.width
+    }
+}
+
+impl Perimeter for Rectangle {
+    fn perimeter(&self) -> f64 {
+        2.0 * (self.length + self.width)
+    }
+}
+
+impl Shape for Rectangle {
+    fn name(&self) -> &'static str {
+        "rectangle"
+    }
+}
+
+/// A triangle described by its three side lengths.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Triangle {
+    a: f64,
+    b: f64,
+    c: f64,
+}
+
+impl Triangle {
+    pub fn new(a: f64, b: f64, c: f64) -> Result<Triangle, ShapeError> {
+        let (a, b, c) = (check_dimension("a", a)?, check_dimension("b", b)?, check_dimension("c", c)?);
+
+        if a + b <= c || a + c <= b || b + c <= a {
+            return Err(ShapeError::InvalidTriangle { a, b, c });
+        }
+
+        Ok(Triangle { a, b, c })
+    }
+
+    pub fn sides(&self) -> (f64, f64, f64) {
+        (self.a, self.b, self.c)
+    }
+}
+
+impl Area for Triangle {
+    // Heron's formula.
+    fn area(&self) -> f64 {
+        let s = self.perimeter() / 2.0;
+        (s * (s - self.a) * (s - self.b) * (s - self.c)).sqrt()
+    }
+}
+
+impl Perimeter for Triangle {
+    fn perimeter(&self) -> f64 {
+        self.a + self.b + self.c
+    }
+}
+
+impl Shape for Triangle {
+    fn name(&self) -> &'static str {
+        "triangle"
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ellipse {
+    semi_major: f64,
+    semi_minor: f64,
+}
+
+impl Ellipse {
+    /// The axes may be given in either order.
+    pub fn new(a: f64, b: f64) -> Result<Ellipse, ShapeError> {
+        let (a, b) = (check_dimension("semi-axis", a)?, check_dimension("semi-axis", b)?);
+
+        Ok(Ellipse { semi_major: a.max(b), semi_minor: a.min(b) })
+    }
+
+    pub fn semi_major(&self) -> f64 {
+        self.semi_major
+    }
+
+    pub fn semi_minor(&self) -> f64 {
+        self.semi_minor
+    }
+}
+
+impl Area for Ellipse {
+    fn area(&self) -> f64 {
+        PI * self.semi_major * self.semi_minor
+    }
+}
+
+impl Perimeter for Ellipse {
+    // Ramanujan's second approximation; exact for circles.
+    fn perimeter(&self) -> f64 {
+        let (a, b) = (self.semi_major, self.semi_minor);
+        let h = ((a - b) / (a + b)).powi(2);
+
+        PI * (a + b) * (1.0 + 3.0 * h / (10.0 + (4.0 - 3.0 * h).sqrt()))
+    }
+}
+
+impl Shape for Ellipse {
+    fn name(&self) -> &'static str {
+        "ellipse"
+    }
+}
+
+/// A polygon with `sides` equal sides and equal angles.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RegularPolygon {
+    sides: usize,
+    side_length: f64,
+}
+
+impl RegularPolygon {
+    pub fn new(sides: usize, side_length: f64) -> Result<RegularPolygon, ShapeError> {
+        if sides < 3 {
+            return Err(ShapeError::TooFewSides(sides));
+        }
+
+        Ok(RegularPolygon { sides, side_length: check_dimension("side length", side_length)? })
+    }
+
+    pub fn sides(&self) -> usize {
+        self.sides
+    }
+
+    pub fn side_length(&self) -> f64 {
+        self.side_length
+    }
+}
+
+impl Area for RegularPolygon {
+    fn area(&self) -> f64 {
+        let n = self.sides as f64;
+        n * self.side_length.powi(2) / (4.0 * (PI / n).tan())
+    }
+}
+
+impl Perimeter for RegularPolygon {
+    fn perimeter(&self) -> f64 {
+        self.sides as f64 * self.side_length
+    }
+}
+
+impl Shape for RegularPolygon {
+    fn name(&self) -> &'static str {
+        "regular polygon"
+    }
+}
+
+/// A simple polygon given by its vertices in order, either clockwise or
+/// anticlockwise. Edges may only meet their neighbours, at the vertex they
+/// share.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Polygon {
+    vertices: Vec<Point>,
+}
+
+impl Polygon {
+    pub fn new(vertices: Vec<Point>) -> Result<Polygon, ShapeError> {
+        if vertices.len() < 3 {
+            return Err(ShapeError::TooFewSides(vertices.len()));
+        }
+
+        for v in &vertices {
+            if !v.x.is_finite() || !v.y.is_finite() {
+                let value = if v.x.is_finite() { v.y } else { v.x };
+                return Err(ShapeError::InvalidDimension { name: "vertex coordinate", value });
+            }
+        }
+
+        let polygon = Polygon { vertices };
+        if polygon.crosses_itself() {
+            return Err(ShapeError::SelfIntersecting);
+        }
+        if polygon.area() <= EPSILON {
+            return Err(ShapeError::Degenerate);
+        }
+
+        Ok(polygon)
+    }
+
+    pub fn vertices(&self) -> &[Point] {
+        &self.vertices
+    }
+
+    // Each vertex paired with the next, wrapping around to the first.
+    fn edges(&self) -> impl Iterator<Item = (&Point, &Point)> {
+        self.vertices.iter().zip(self.vertices.iter().cycle().skip(1))
+    }
+
+    // Compares every pair of edges that are not neighbours, the first and
+    // last included, which is quadratic but fine for hand-written shapes.
+    fn crosses_itself(&self) -> bool {
+        let edges: Vec<(Point, Point)> = self.edges().map(|(&p, &q)| (p, q)).collect();
+        let n = edges.len();
+
+        (0..n).any(|i| {
+            (i + 2..n)
+                .filter(|&j| !(i == 0 && j == n - 1))
+                .any(|j| segments_intersect(edges[i].0, edges[i].1, edges[j].0, edges[j].1))
+        })
+    }
+}
+
+impl Area for Polygon {
+    // Shoelace formula.
+    fn area(&self) -> f64 {
+        let twice_area: f64 = self.edges().map(|(p, q)| p.x * q.y - q.x * p.y).sum();
+        twice_area.abs() / 2.0
+    }
+}
+
+impl Perimeter for Polygon {
+    fn perimeter(&self) -> f64 {
+        self.edges().map(|(p, q)| p.distance(q)).sum()
+    }
+}
+
+impl Shape for Polygon {
+    fn name(&self) -> &'static str {
+        "polygon"
+    }
+}
+
+/// Any one of the shapes in this module, for collections that need to know
+/// the concrete shape later, e.g. to print it back out.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AnyShape {
+    Circle(Circle),
+    Square(Square),
+    Rectangle(Rectangle),
+    Triangle(Triangle),
+    Ellipse(Ellipse),
+    RegularPolygon(RegularPolygon),
+    Polygon(Polygon),
+}
+
+impl AnyShape {
+    fn as_shape(&self) -> &dyn Shape {
+        match self {
+            AnyShape::Circle(s) => s,
+            AnyShape::Square(s) => s,
+            AnyShape::Rectangle(s) => s,
+            AnyShape::Triangle(s) => s,
+            AnyShape::Ellipse(s) => s,
+            AnyShape::RegularPolygon(s) => s,
+            AnyShape::Polygon(s) => s,
+        }
+    }
+}
+
+impl Area for AnyShape {
+    fn area(&self) -> f64 {
+        self.as_shape().area()
+    }
+}
+
+impl Perimeter for AnyShape {
+    fn perimeter(&self) -> f64 {
+        self.as_shape().perimeter()
+    }
+}
+
+impl Geometry for AnyShape {
+    fn outline(&self) -> Outline {
+        self.as_shape().outline()
+    }
+}
+
+impl Shape for AnyShape {
+    fn name(&self) -> &'static str {
+        self.as_shape().name()
+    }
+}
+
+macro_rules! impl_from_shape {
+    ($($variant:ident),*) => {
+        $(
+            impl From<$variant> for AnyShape {
+                fn from(shape: $variant) -> AnyShape {
+                    AnyShape::$variant(shape)
+                }
+            }
+        )*
+    };
+}
+
+impl_from_shape!(Circle, Square, Rectangle, Triangle, Ellipse, RegularPolygon, Polygon);
+
+pub fn print_area<T: Area>(shape: T) {
+    println!("Area of shape: {}", shape.area())
+}
+
+/// Sum of the areas of a mixed collection of shapes.
+pub fn total_area(shapes: &[Box<dyn Shape>]) -> f64 {
+    shapes.iter().map(|shape| shape.area()).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "expected {}, got {}", expected, actual);
+    }
+
+    #[test]
+    fn should_reject_invalid_dimensions() {
+        assert_eq!(Circle::new(-1.0), Err(ShapeError::InvalidDimension { name: "radius", value: -1.0 }));
+        assert!(Square::new(0.0).is_err());
+        assert!(Rectangle::new(2.0, f64::NAN).is_err());
+        assert!(Ellipse::new(f64::INFINITY, 1.0).is_err());
+        assert_eq!(Triangle::new(1.0, 2.0, 3.0), Err(ShapeError::InvalidTriangle { a: 1.0, b: 2.0, c: 3.0 }));
+        assert_eq!(RegularPolygon::new(2, 1.0), Err(ShapeError::TooFewSides(2)));
+    }
+
+    #[test]
+    fn should_compute_area_and_perimeter() {
+        let rectangle = Rectangle::new(2.0, 5.0).unwrap();
+        let square = Square::new(3.0).unwrap();
+        let triangle = Triangle::new(3.0, 4.0, 5.0).unwrap();
+
+        assert_close(rectangle.area(), 10.0);
+        assert_close(rectangle.perimeter(), 14.0);
+        assert_close(square.area(), 9.0);
+        assert_close(square.perimeter(), 12.0);
+        assert_close(triangle.area(), 6.0);
+        assert_close(triangle.perimeter(), 12.0);
+    }
+
+    #[test]
+    fn should_treat_equal_axes_ellipse_as_circle() {
+        let ellipse = Ellipse::new(2.0, 2.0).unwrap();
+        let circle = Circle::new(2.0).unwrap();
+
+        assert_close(ellipse.area(), circle.area());
+        assert_close(ellipse.perimeter(), circle.perimeter());
+        assert_eq!(Ellipse::new(1.0, 3.0).unwrap().semi_major(), 3.0);
+    }
+
+    #[test]
+    fn should_match_regular_polygon_with_square() {
+        let polygon = RegularPolygon::new(4, 3.0).unwrap();
+
+        assert_close(polygon.area(), 9.0);
+        assert_close(polygon.perimeter(), 12.0);
+    }
+
+    #[test]
+    fn should_use_shoelace_formula_for_polygons() {
+        let l_shape = Polygon::new(vec![
+            Point::new(0.0, 0.0),
+            Point::new(2.0, 0.0),
+            Point::new(2.0, 1.0),
+            Point::new(1.0, 1.0),
+            Point::new(1.0, 2.0),
+            Point::new(0.0, 2.0),
+        ])
+        .unwrap();
+        let clockwise = Polygon::new(vec![Point::new(0.0, 0.0), Point::new(0.0, 3.0), Point::new(4.0, 0.0)]).unwrap();
+
+        assert_close(l_shape.area(), 3.0);
+        assert_close(l_shape.perimeter(), 8.0);
+        assert_close(clockwise.area(), 6.0);
+        assert_close(clockwise.perimeter(), 12.0);
+    }
+
+    #[test]
+    fn should_reject_degenerate_polygons() {
+        let line = vec![Point::new(0.0, 0.0), Point::new(1.0, 1.0), Point::new(2.0, 2.0)];
+
+        assert_eq!(Polygon::new(line), Err(ShapeError::Degenerate));
+        assert_eq!(Polygon::new(vec![Point::new(0.0, 0.0)]), Err(ShapeError::TooFewSides(1)));
+
+        let nearly_a_line = vec![Point::new(0.0, 0.0), Point::new(1.0, 0.0), Point::new(2.0, 1e-12)];
+        assert_eq!(Polygon::new(nearly_a_line), Err(ShapeError::Degenerate));
+    }
+
+    #[test]
+    fn should_reject_self_intersecting_polygons() {
+        // A bow-tie whose lobes differ in size, so the shoelace sum does not
+        // cancel out.
+        let bow_tie = vec![Point::new(0.0, 0.0), Point::new(2.0, 2.0), Point::new(2.0, 0.0), Point::new(0.0, 3.0)];
+        let touching = vec![
+            Point::new(0.0, 0.0),
+            Point::new(4.0, 0.0),
+            Point::new(2.0, 2.0),
+            Point::new(4.0, 4.0),
+            Point::new(0.0, 4.0),
+            Point::new(2.0, 0.0),
+        ];
+
+        assert_eq!(Polygon::new(bow_tie), Err(ShapeError::SelfIntersecting));
+        assert_eq!(Polygon::new(touching), Err(ShapeError::SelfIntersecting));
+    }
+
+    #[test]
+    fn should_total_mixed_shapes() {
+        let shapes: Vec<Box<dyn Shape>> = vec![
+            Box::new(Square::new(2.0).unwrap()),
+            Box::new(Rectangle::new(1.0, 3.0).unwrap()),
+            Box::new(Triangle::new(3.0, 4.0, 5.0).unwrap()),
+            Box::new(Circle::new(1.0).unwrap()),
+        ];
+
+        assert_close(total_area(&shapes), 4.0 + 3.0 + 6.0 + PI);
+        assert_eq!(shapes.iter().map(|s| s.name()).collect::<Vec<_>>(), vec!["square", "rectangle", "triangle", "circle"]);
+    }
+}
@@ -0,0 +1,582 @@
+//! A small text language for scenes of shapes, plus an equivalent JSON form.
+//!
+//! Each statement is a shape name followed by `key=value` dimensions, and
+//! statements are separated by `;` or new lines. `#` starts a comment.
+//!
+//! ```text
+//! circle r=3; rect w=2 l=5 at=4,0
+//! square s=1.5 rot=45          # degrees
+//! triangle a=3 b=4 c=5
+//! ellipse a=4 b=2 scale=0.5
+//! ngon n=6 s=1
+//! polygon 0,0 4,0 4,3
+//! ```
+//!
+//! Every shape also accepts `at=x,y`, `rot=degrees` and `scale=factor` to
+//! place it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use serde::de::{self, MapAccess, Visitor};
+use serde::de::value::MapAccessDeserializer;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+
+use super::geometry::{Placed, Transform};
+use super::{AnyShape, Circle, Ellipse, Point, Polygon, Rectangle, RegularPolygon, ShapeError, Square, Triangle};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ErrorKind {
+    UnknownShape(String),
+    UnknownKey(String),
+    DuplicateKey(String),
+    MissingKey(&'static str),
+    InvalidValue { key: String, value: String },
+    /// The dimensions parsed but do not make a valid shape, e.g. `r=-1`.
+    Shape(ShapeError),
+    /// Malformed or invalid JSON; the message comes from `serde_json`.
+    Json(String),
+}
+
+/// A problem in a scene description, with the line it was found on.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub line: usize,
+    pub kind: ErrorKind,
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ErrorKind::UnknownShape(name) => write!(f, "unknown shape '{}'", name),
+            ErrorKind::UnknownKey(key) => write!(f, "unknown key '{}'", key),
+            ErrorKind::DuplicateKey(key) => write!(f, "'{}' is given more than once", key),
+            ErrorKind::MissingKey(key) => write!(f, "missing '{}'", key),
+            ErrorKind::InvalidValue { key, value } => write!(f, "invalid value '{}' for '{}'", value, key),
+            ErrorKind::Shape(e) => write!(f, "{}", e),
+            ErrorKind::Json(message) => write!(f, "{}", message),
+        }
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.kind)
+    }
+}
+
+impl Error for ParseError {}
+
+#[derive(Debug)]
+pub enum SceneError {
+    Io(io::Error),
+    Parse(ParseError),
+}
+
+impl fmt::Display for SceneError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SceneError::Io(e) => write!(f, "could not access scene file: {}", e),
+            SceneError::Parse(e) => write!(f, "invalid scene: {}", e),
+        }
+    }
+}
+
+impl Error for SceneError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SceneError::Io(e) => Some(e),
+            SceneError::Parse(e) => Some(e),
+        }
+    }
+}
+
+impl From<io::Error> for SceneError {
+    fn from(e: io::Error) -> SceneError {
+        SceneError::Io(e)
+    }
+}
+
+impl From<ParseError> for SceneError {
+    fn from(e: ParseError) -> SceneError {
+        SceneError::Parse(e)
+    }
+}
+
+// The key=value pairs of one statement, consumed as the shape is built so
+// leftovers can be reported as unknown.
+struct Fields<'a> {
+    line: usize,
+    values: HashMap<&'a str, &'a str>,
+}
+
+impl<'a> Fields<'a> {
+    fn error(&self, kind: ErrorKind) -> ParseError {
+        ParseError { line: self.line, kind }
+    }
+
+    fn invalid(&self, key: &str, value: &str) -> ParseError {
+        self.error(ErrorKind::InvalidValue { key: key.to_owned(), value: value.to_owned() })
+    }
+
+    fn take(&mut self, key: &str) -> Option<&'a str> {
+        self.values.remove(key)
+    }
+
+    fn number(&mut self, key: &'static str) -> Result<f64, ParseError> {
+        let value = self.take(key).ok_or(self.error(ErrorKind::MissingKey(key)))?;
+        finite(value).ok_or(self.invalid(key, value))
+    }
+
+    fn optional_number(&mut self, key: &'static str) -> Result<Option<f64>, ParseError> {
+        match self.take(key) {
+            Some(value) => finite(value).map(Some).ok_or(self.invalid(key, value)),
+            None => Ok(None),
+        }
+    }
+
+    fn point(&self, key: &str, value: &str) -> Result<Point, ParseError> {
+        let (x, y) = value.split_once(',').ok_or(self.invalid(key, value))?;
+
+        match (finite(x), finite(y)) {
+            (Some(x), Some(y)) => Ok(Point::new(x, y)),
+            _ => Err(self.invalid(key, value)),
+        }
+    }
+}
+
+// `f64::from_str` also accepts `inf` and `NaN`, which no dimension or
+// placement can use.
+fn finite(value: &str) -> Option<f64> {
+    value.parse().ok().filter(|v: &f64| v.is_finite())
+}
+
+fn parse_statement(statement: &str, line: usize) -> Result<Placed<AnyShape>, ParseError> {
+    let mut tokens = statement.split_whitespace();
+    let name = tokens.next().unwrap_or_default();
+    let mut fields = Fields { line, values: HashMap::new() };
+    let mut points = Vec::new();
+
+    for token in tokens {
+        match token.split_once('=') {
+            Some((key, value)) => {
+                if fields.values.insert(key, value).is_some() {
+                    return Err(fields.error(ErrorKind::DuplicateKey(key.to_owned())));
+                }
+            }
+            None if name == "polygon" => points.push(fields.point("point", token)?),
+            None => return Err(fields.error(ErrorKind::InvalidValue { key: name.to_owned(), value: token.to_owned() })),
+        }
+    }
+
+    let shape: Result<AnyShape, ShapeError> = match name {
+        "circle" => Circle::new(fields.number("r")?).map(AnyShape::from),
+        "square" => Square::new(fields.number("s")?).map(AnyShape::from),
+        "rect" => Rectangle::new(fields.number("w")?, fields.number("l")?).map(AnyShape::from),
+        "triangle" => {
+            Triangle::new(fields.number("a")?, fields.number("b")?, fields.number("c")?).map(AnyShape::from)
+        }
+        "ellipse" => Ellipse::new(fields.number("a")?, fields.number("b")?).map(AnyShape::from),
+        "ngon" => {
+            let n = fields.take("n").ok_or(fields.error(ErrorKind::MissingKey("n")))?;
+            let n = n.parse().map_err(|_| fields.invalid("n", n))?;
+            RegularPolygon::new(n, fields.number("s")?).map(AnyShape::from)
+        }
+        "polygon" => Polygon::new(points).map(AnyShape::from),
+        _ => return Err(fields.error(ErrorKind::UnknownShape(name.to_owned()))),
+    };
+    let shape = shape.map_err(|e| fields.error(ErrorKind::Shape(e)))?;
+
+    let mut placed = Placed::new(shape);
+    if let Some(at) = fields.take("at") {
+        let at = fields.point("at", at)?;
+        placed = placed.translate(at.x, at.y);
+    }
+    if let Some(degrees) = fields.optional_number("rot")? {
+        placed = placed.rotate(degrees.to_radians());
+    }
+    if let Some(factor) = fields.optional_number("scale")? {
+        placed = placed.scale(factor).map_err(|e| fields.error(ErrorKind::Shape(e)))?;
+    }
+
+    if let Some(key) = fields.values.keys().min() {
+        return Err(fields.error(ErrorKind::UnknownKey(key.to_string())));
+    }
+
+    Ok(placed)
+}
+
+/// Parses a scene description into placed shapes, in order.
+pub fn parse_scene(text: &str) -> Result<Vec<Placed<AnyShape>>, ParseError> {
+    let mut shapes = Vec::new();
+
+    for (index, line) in text.lines().enumerate() {
+        let line_text = line.split('#').next().unwrap_or_default();
+
+        for statement in line_text.split(';').map(str::trim).filter(|s| !s.is_empty()) {
+            shapes.push(parse_statement(statement, index + 1)?);
+        }
+    }
+
+    Ok(shapes)
+}
+
+// Degrees come back from radians with rounding noise, e.g. 45.00000000000001.
+fn format_degrees(radians: f64) -> String {
+    let degrees = (radians.to_degrees() * 1e9).round() / 1e9;
+    format!("{}", degrees)
+}
+
+fn format_shape(shape: &AnyShape) -> String {
+    match shape {
+        AnyShape::Circle(c) => format!("circle r={}", c.radius),
+        AnyShape::Square(s) => format!("square s={}", s.side()),
+        AnyShape::Rectangle(r) => format!("rect w={} l={}", r.width(), r.length()),
+        AnyShape::Triangle(t) => {
+            let (a, b, c) = t.sides();
+            format!("triangle a={} b={} c={}", a, b, c)
+        }
+        AnyShape::Ellipse(e) => format!("ellipse a={} b={}", e.semi_major(), e.semi_minor()),
+        AnyShape::RegularPolygon(p) => format!("ngon n={} s={}", p.sides(), p.side_length()),
+        AnyShape::Polygon(p) => {
+            let points: Vec<String> = p.vertices().iter().map(|v| format!("{},{}", v.x, v.y)).collect();
+            format!("polygon {}", points.join(" "))
+        }
+    }
+}
+
+/// Writes shapes back out in the text form, one per line. Placement keys are
+/// only written when they differ from the default.
+pub fn print_scene(shapes: &[Placed<AnyShape>]) -> String {
+    let mut out = String::new();
+
+    for placed in shapes {
+        let Transform { translation, rotation, scale } = placed.transform;
+        out.push_str(&format_shape(&placed.shape));
+
+        if translation != Point::new(0.0, 0.0) {
+            out.push_str(&format!(" at={},{}", translation.x, translation.y));
+        }
+        if rotation != 0.0 {
+            out.push_str(&format!(" rot={}", format_degrees(rotation)));
+        }
+        if scale != 1.0 {
+            out.push_str(&format!(" scale={}", scale));
+        }
+        out.push('\n');
+    }
+
+    out
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "shape", rename_all = "snake_case")]
+enum ShapeRecord {
+    Circle { r: f64 },
+    Square { s: f64 },
+    Rect { w: f64, l: f64 },
+    Triangle { a: f64, b: f64, c: f64 },
+    Ellipse { a: f64, b: f64 },
+    Ngon { n: usize, s: f64 },
+    Polygon { points: Vec<[f64; 2]> },
+}
+
+fn is_origin(at: &[f64; 2]) -> bool {
+    *at == [0.0, 0.0]
+}
+
+fn is_zero(rot: &f64) -> bool {
+    *rot == 0.0
+}
+
+fn is_one(scale: &f64) -> bool {
+    *scale == 1.0
+}
+
+fn one() -> f64 {
+    1.0
+}
+
+// One JSON array element.
+#[derive(Clone, Serialize)]
+#[serde(into = "PlacedRecord")]
+struct JsonShape(Placed<AnyShape>);
+
+// Validating inside `visit_map`, rather than with `#[serde(try_from)]`, makes
+// serde_json report the line the offending object ends on instead of wherever
+// the array happened to be when the error surfaced.
+impl<'de> Deserialize<'de> for JsonShape {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<JsonShape, D::Error> {
+        struct ShapeVisitor;
+
+        impl<'de> Visitor<'de> for ShapeVisitor {
+            type Value = JsonShape;
+
+            fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str("a shape object")
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<JsonShape, A::Error> {
+                let record = PlacedRecord::deserialize(MapAccessDeserializer::new(map))?;
+                JsonShape::try_from(record).map_err(de::Error::custom)
+            }
+        }
+
+        deserializer.deserialize_map(ShapeVisitor)
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct PlacedRecord {
+    #[serde(flatten)]
+    shape: ShapeRecord,
+    #[serde(default, skip_serializing_if = "is_origin")]
+    at: [f64; 2],
+    /// Degrees, as in the text form.
+    #[serde(default, skip_serializing_if = "is_zero")]
+    rot: f64,
+    #[serde(default = "one", skip_serializing_if = "is_one")]
+    scale: f64,
+    /// Every key not listed above, including the shape's own, since
+    /// `deny_unknown_fields` does not work together with `flatten`.
+    #[serde(flatten, skip_serializing)]
+    extra: BTreeMap<String, Value>,
+}
+
+impl TryFrom<PlacedRecord> for JsonShape {
+    type Error = ErrorKind;
+
+    fn try_from(record: PlacedRecord) -> Result<JsonShape, ErrorKind> {
+        let known = record.shape.keys();
+        if let Some(key) = record.extra.keys().find(|key| *key != "shape" && !known.contains(&key.as_str())) {
+            return Err(ErrorKind::UnknownKey(key.clone()));
+        }
+
+        let shape = record.shape.into_shape().map_err(ErrorKind::Shape)?;
+        let placed = Placed::at(shape, record.at[0], record.at[1]).rotate(record.rot.to_radians());
+        Ok(JsonShape(placed.scale(record.scale).map_err(ErrorKind::Shape)?))
+    }
+}
+
+impl ShapeRecord {
+    fn keys(&self) -> &'static [&'static str] {
+        match self {
+            ShapeRecord::Circle { .. } => &["r"],
+            ShapeRecord::Square { .. } => &["s"],
+            ShapeRecord::Rect { .. } => &["w", "l"],
+            ShapeRecord::Triangle { .. } => &["a", "b", "c"],
+            ShapeRecord::Ellipse { .. } => &["a", "b"],
+            ShapeRecord::Ngon { .. } => &["n", "s"],
+            ShapeRecord::Polygon { .. } => &["points"],
+        }
+    }
+
+    fn into_shape(self) -> Result<AnyShape, ShapeError> {
+        let shape: AnyShape = match self {
+            ShapeRecord::Circle { r } => Circle::new(r)?.into(),
+            ShapeRecord::Square { s } => Square::new(s)?.into(),
+            ShapeRecord::Rect { w, l } => Rectangle::new(w, l)?.into(),
+            ShapeRecord::Triangle { a, b, c } => Triangle::new(a, b, c)?.into(),
+            ShapeRecord::Ellipse { a, b } => Ellipse::new(a, b)?.into(),
+            ShapeRecord::Ngon { n, s } => RegularPolygon::new(n, s)?.into(),
+            ShapeRecord::Polygon { points } => {
+                Polygon::new(points.iter().map(|&[x, y]| Point::new(x, y)).collect())?.into()
+            }
+        };
+        Ok(shape)
+    }
+}
+
+impl From<JsonShape> for PlacedRecord {
+    fn from(JsonShape(placed): JsonShape) -> PlacedRecord {
+        let shape = match placed.shape {
+            AnyShape::Circle(c) => ShapeRecord::Circle { r: c.radius },
+            AnyShape::Square(s) => ShapeRecord::Square { s: s.side() },
+            AnyShape::Rectangle(r) => ShapeRecord::Rect { w: r.width(), l: r.length() },
+            AnyShape::Triangle(t) => {
+                let (a, b, c) = t.sides();
+                ShapeRecord::Triangle { a, b, c }
+            }
+            AnyShape::Ellipse(e) => ShapeRecord::Ellipse { a: e.semi_major(), b: e.semi_minor() },
+            AnyShape::RegularPolygon(p) => ShapeRecord::Ngon { n: p.sides(), s: p.side_length() },
+            AnyShape::Polygon(p) => ShapeRecord::Polygon { points: p.vertices().iter().map(|v| [v.x, v.y]).collect() },
+        };
+        let Transform { translation, rotation, scale } = placed.transform;
+        let rot = format_degrees(rotation).parse().unwrap_or_else(|_| rotation.to_degrees());
+
+        PlacedRecord { shape, at: [translation.x, translation.y], rot, scale, extra: BTreeMap::new() }
+    }
+}
+
+pub fn scene_to_json(shapes: &[Placed<AnyShape>]) -> String {
+    let records: Vec<JsonShape> = shapes.iter().cloned().map(JsonShape).collect();
+    serde_json::to_string_pretty(&records).expect("shapes always serialize")
+}
+
+pub fn scene_from_json(json: &str) -> Result<Vec<Placed<AnyShape>>, ParseError> {
+    let records: Vec<JsonShape> = serde_json::from_str(json)
+        .map_err(|e| ParseError { line: e.line(), kind: ErrorKind::Json(e.to_string()) })?;
+
+    Ok(records.into_iter().map(|JsonShape(placed)| placed).collect())
+}
+
+fn is_json(path: &Path) -> bool {
+    path.extension().is_some_and(|ext| ext.eq_ignore_ascii_case("json"))
+}
+
+/// Reads a scene, using the JSON form for `.json` files and the text form
+/// for anything else.
+pub fn load_scene<P: AsRef<Path>>(path: P) -> Result<Vec<Placed<AnyShape>>, SceneError> {
+    let contents = fs::read_to_string(path.as_ref())?;
+
+    let shapes = if is_json(path.as_ref()) { scene_from_json(&contents)? } else { parse_scene(&contents)? };
+    Ok(shapes)
+}
+
+pub fn save_scene<P: AsRef<Path>>(path: P, shapes: &[Placed<AnyShape>]) -> Result<(), SceneError> {
+    let contents = if is_json(path.as_ref()) { scene_to_json(shapes) } else { print_scene(shapes) };
+
+    Ok(fs::write(path, contents)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::trait_example::{Area, Shape};
+
+    const SCENE: &str = "\
+circle r=3; rect w=2 l=5 at=4,0
+# a comment line
+
+square s=1.5 rot=45   # trailing comment
+triangle a=3 b=4 c=5
+ellipse a=4 b=2 scale=0.5
+ngon n=6 s=1 at=-1.5,2.25
+polygon 0,0 4,0 4,3
+";
+
+    #[test]
+    fn should_parse_every_shape_kind() {
+        let shapes = parse_scene(SCENE).unwrap();
+
+        let names: Vec<&str> = shapes.iter().map(|p| p.shape.name()).collect();
+        assert_eq!(names, vec!["circle", "rectangle", "square", "triangle", "ellipse", "regular polygon", "polygon"]);
+        assert_eq!(shapes[1].transform.translation, Point::new(4.0, 0.0));
+        assert_eq!(shapes[2].transform.rotation, 45f64.to_radians());
+        assert_eq!(shapes[4].transform.scale, 0.5);
+        assert_eq!(shapes[6].shape.area(), 6.0);
+    }
+
+    #[test]
+    fn should_print_what_it_parses() {
+        let printed = print_scene(&parse_scene(SCENE).unwrap());
+
+        assert_eq!(
+            printed,
+            "\
+circle r=3
+rect w=2 l=5 at=4,0
+square s=1.5 rot=45
+triangle a=3 b=4 c=5
+ellipse a=4 b=2 scale=0.5
+ngon n=6 s=1 at=-1.5,2.25
+polygon 0,0 4,0 4,3
+"
+        );
+        assert_eq!(parse_scene(&printed).unwrap(), parse_scene(SCENE).unwrap());
+    }
+
+    #[test]
+    fn should_report_line_of_errors() {
+        let error = |text| parse_scene(text).unwrap_err();
+
+        assert_eq!(
+            error("circle r=1\n\nhexagon s=2"),
+            ParseError { line: 3, kind: ErrorKind::UnknownShape("hexagon".into()) }
+        );
+        assert_eq!(
+            error("circle r=1; rect w=-2 l=5"),
+            ParseError { line: 1, kind: ErrorKind::Shape(ShapeError::InvalidDimension { name: "width", value: -2.0 }) }
+        );
+        assert_eq!(error("# header\nrect w=2"), ParseError { line: 2, kind: ErrorKind::MissingKey("l") });
+        assert_eq!(error("circle r=1 colour=red"), ParseError { line: 1, kind: ErrorKind::UnknownKey("colour".into()) });
+        assert_eq!(error("circle r=1 r=2"), ParseError { line: 1, kind: ErrorKind::DuplicateKey("r".into()) });
+        assert_eq!(
+            error("circle r=big"),
+            ParseError { line: 1, kind: ErrorKind::InvalidValue { key: "r".into(), value: "big".into() } }
+        );
+        assert_eq!(error("circle r=1 scale=0").to_string(), "line 1: scale factor must be a positive finite number, got 0");
+    }
+
+    #[test]
+    fn should_reject_non_finite_placements() {
+        let invalid = |key: &str, value: &str| ParseError {
+            line: 1,
+            kind: ErrorKind::InvalidValue { key: key.into(), value: value.into() },
+        };
+
+        assert_eq!(parse_scene("circle r=1 at=inf,0").unwrap_err(), invalid("at", "inf,0"));
+        assert_eq!(parse_scene("circle r=1 rot=NaN").unwrap_err(), invalid("rot", "NaN"));
+        assert_eq!(parse_scene("circle r=1 scale=infinity").unwrap_err(), invalid("scale", "infinity"));
+    }
+
+    #[test]
+    fn should_round_trip_through_json() {
+        let shapes = parse_scene(SCENE).unwrap();
+
+        let json = scene_to_json(&shapes);
+
+        assert!(json.contains("\"shape\": \"rect\""));
+        assert_eq!(scene_from_json(&json).unwrap(), shapes);
+    }
+
+    #[test]
+    fn should_report_json_errors_with_line() {
+        let json = r#"[
+  {"shape": "circle", "r": 1},
+  {"shape": "square", "s": -4},
+  {"shape": "circle", "r": 2}
+]"#;
+
+        let err = scene_from_json(json).unwrap_err();
+
+        assert_eq!(err.line, 3);
+        assert!(err.to_string().contains("side must be a positive finite number"));
+        assert!(matches!(scene_from_json(r#"[{"shape": "blob"}]"#).unwrap_err().kind, ErrorKind::Json(_)));
+    }
+
+    #[test]
+    fn should_reject_unknown_json_keys() {
+        let json = r#"[
+  {"shape": "circle", "r": 1, "rot": 45},
+  {"shape": "square", "s": 2, "rott": 45}
+]"#;
+
+        let err = scene_from_json(json).unwrap_err();
+
+        assert_eq!(err.line, 3);
+        assert!(err.to_string().contains("unknown key 'rott'"));
+        assert!(scene_from_json(r#"[{"shape": "rect", "w": 1, "l": 2, "r": 3}]"#).is_err());
+    }
+
+    #[test]
+    fn should_load_and_save_by_extension() {
+        let shapes = parse_scene("circle r=2 at=1,1\nngon n=5 s=2").unwrap();
+        let dir = std::env::temp_dir();
+
+        for name in ["scene.txt", "scene.json"] {
+            let path = dir.join(format!("{}-{}", std::process::id(), name));
+            save_scene(&path, &shapes).unwrap();
+            let loaded = load_scene(&path).unwrap();
+            fs::remove_file(&path).unwrap();
+
+            assert_eq!(loaded, shapes);
+        }
+        assert!(matches!(load_scene("no/such/scene.txt"), Err(SceneError::Io(_))));
+    }
+}
@@ -0,0 +1,562 @@
+use std::f64::consts::PI;
+
+use super::{
+    check_dimension, Circle, Ellipse, Point, Polygon, Rectangle, RegularPolygon, ShapeError, Square,
+    Triangle,
+};
+
+/// Tolerance for floating point comparisons: points this close to an edge
+/// count as on it, and shapes this close to each other count as touching.
+pub const EPSILON: f64 = 1e-9;
+
+/// Number of edges used when an ellipse has to be approximated by a polygon.
+const ELLIPSE_SEGMENTS: usize = 720;
+
+pub fn approx_eq(a: f64, b: f64) -> bool {
+    (a - b).abs() <= EPSILON * a.abs().max(b.abs()).max(1.0)
+}
+
+// `a <= b`, with the same tolerance as `approx_eq`.
+fn approx_le(a: f64, b: f64) -> bool {
+    a <= b || approx_eq(a, b)
+}
+
+/// An axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+    pub min: Point,
+    pub max: Point,
+}
+
+impl Aabb {
+    /// The smallest box containing every point, or `None` if there are none.
+    pub fn from_points<I: IntoIterator<Item = Point>>(points: I) -> Option<Aabb> {
+        points.into_iter().fold(None, |aabb, p| {
+            Some(match aabb {
+                None => Aabb { min: p, max: p },
+                Some(Aabb { min, max }) => Aabb {
+                    min: Point::new(min.x.min(p.x), min.y.min(p.y)),
+                    max: Point::new(max.x.max(p.x), max.y.max(p.y)),
+                },
+            })
+        })
+    }
+
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        approx_le(self.min.x, p.x)
+            && approx_le(p.x, self.max.x)
+            && approx_le(self.min.y, p.y)
+            && approx_le(p.y, self.max.y)
+    }
+
+    pub fn intersects(&self, other: &Aabb) -> bool {
+        approx_le(self.min.x, other.max.x)
+            && approx_le(other.min.x, self.max.x)
+            && approx_le(self.min.y, other.max.y)
+            && approx_le(other.min.y, self.max.y)
+    }
+
+    pub fn union(&self, other: &Aabb) -> Aabb {
+        Aabb {
+            min: Point::new(self.min.x.min(other.min.x), self.min.y.min(other.min.y)),
+            max: Point::new(self.max.x.max(other.max.x), self.max.y.max(other.max.y)),
+        }
+    }
+
+    pub fn approx_eq(&self, other: &Aabb) -> bool {
+        approx_eq(self.min.x, other.min.x)
+            && approx_eq(self.min.y, other.min.y)
+            && approx_eq(self.max.x, other.max.x)
+            && approx_eq(self.max.y, other.max.y)
+    }
+}
+
+/// A shape reduced to one of the two primitives the geometry code works on.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Outline {
+    /// Semi-axes `a` along and `b` across the `rotation` direction (radians).
+    Ellipse { center: Point, a: f64, b: f64, rotation: f64 },
+    /// Vertices in order, either winding.
+    Polygon(Vec<Point>),
+}
+
+impl Outline {
+    /// `None` for a polygon without vertices.
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        match self {
+            Outline::Ellipse { center, a, b, rotation } => {
+                let (sin, cos) = rotation.sin_cos();
+                let half_width = ((a * cos).powi(2) + (b * sin).powi(2)).sqrt();
+                let half_height = ((a * sin).powi(2) + (b * cos).powi(2)).sqrt();
+
+                Some(Aabb {
+                    min: Point::new(center.x - half_width, center.y - half_height),
+                    max: Point::new(center.x + half_width, center.y + half_height),
+                })
+            }
+            Outline::Polygon(vertices) => Aabb::from_points(vertices.iter().copied()),
+        }
+    }
+
+    /// Whether `p` is inside or on the boundary.
+    pub fn contains(&self, p: Point) -> bool {
+        match self {
+            Outline::Ellipse { .. } => {
+                let q = self.to_unit_circle(p);
+                q.x.hypot(q.y) <= 1.0 + EPSILON
+            }
+            Outline::Polygon(vertices) => polygon_contains(vertices, p),
+        }
+    }
+
+    /// Whether the two outlines share at least one point.
+    ///
+    /// Exact for polygons and circles. Between two non-circular ellipses one
+    /// of them is approximated by a 720-sided polygon. A polygon without
+    /// vertices overlaps nothing.
+    pub fn overlaps(&self, other: &Outline) -> bool {
+        let (Some(a), Some(b)) = (self.bounding_box(), other.bounding_box()) else {
+            return false;
+        };
+        if !a.intersects(&b) {
+            return false;
+        }
+
+        match (self, other) {
+            (Outline::Polygon(a), Outline::Polygon(b)) => polygons_overlap(a, b),
+            (ellipse @ Outline::Ellipse { .. }, Outline::Polygon(vertices))
+            | (Outline::Polygon(vertices), ellipse @ Outline::Ellipse { .. }) => {
+                ellipse.overlaps_polygon(vertices)
+            }
+            (
+                Outline::Ellipse { center: c1, a: a1, b: b1, .. },
+                Outline::Ellipse { center: c2, a: a2, b: b2, .. },
+            ) if approx_eq(*a1, *b1) && approx_eq(*a2, *b2) => {
+                c1.distance(c2) <= a1 + a2 + EPSILON
+            }
+            (Outline::Ellipse { .. }, ellipse) => self.overlaps_polygon(&ellipse.to_polygon()),
+        }
+    }
+
+    /// Approximates an ellipse by a polygon; polygons are returned unchanged.
+    pub fn to_polygon(&self) -> Vec<Point> {
+        match self {
+            Outline::Ellipse { center, a, b, rotation } => (0..ELLIPSE_SEGMENTS)
+                .map(|k| {
+                    let t = 2.0 * PI * k as f64 / ELLIPSE_SEGMENTS as f64;
+                    rotate(Point::new(a * t.cos(), b * t.sin()), *rotation).offset(center.x, center.y)
+                })
+                .collect(),
+            Outline::Polygon(vertices) => vertices.clone(),
+        }
+    }
+
+    // Maps `p` into the space where this ellipse is the unit circle.
+    fn to_unit_circle(&self, p: Point) -> Point {
+        match self {
+            Outline::Ellipse { center, a, b, rotation } => {
+                let q = rotate(p.offset(-center.x, -center.y), -rotation);
+                Point::new(q.x / a, q.y / b)
+            }
+            Outline::Polygon(_) => p,
+        }
+    }
+
+    // The ellipse is mapped to the unit circle, which keeps polygons polygons.
+    fn overlaps_polygon(&self, vertices: &[Point]) -> bool {
+        let mapped: Vec<Point> = vertices.iter().map(|&p| self.to_unit_circle(p)).collect();
+        let origin = Point::new(0.0, 0.0);
+
+        polygon_contains(&mapped, origin)
+            || edges(&mapped).any(|(p, q)| segment_distance(origin, p, q) <= 1.0 + EPSILON)
+    }
+}
+
+fn rotate(p: Point, angle: f64) -> Point {
+    let (sin, cos) = angle.sin_cos();
+    Point::new(p.x * cos - p.y * sin, p.x * sin + p.y * cos)
+}
+
+fn edges(vertices: &[Point]) -> impl Iterator<Item = (Point, Point)> + '_ {
+    vertices.iter().copied().zip(vertices.iter().copied().cycle().skip(1))
+}
+
+fn cross(o: Point, a: Point, b: Point) -> f64 {
+    (a.x - o.x) * (b.y - o.y) - (a.y - o.y) * (b.x - o.x)
+}
+
+// Distance from `p` to the segment `a`-`b`.
+fn segment_distance(p: Point, a: Point, b: Point) -> f64 {
+    let (dx, dy) = (b.x - a.x, b.y - a.y);
+    let length_squared = dx * dx + dy * dy;
+
+    if length_squared == 0.0 {
+        return p.distance(&a);
+    }
+
+    let t = (((p.x - a.x) * dx + (p.y - a.y) * dy) / length_squared).clamp(0.0, 1.0);
+    p.distance(&Point::new(a.x + t * dx, a.y + t * dy))
+}
+
+pub(super) fn segments_intersect(p1: Point, p2: Point, q1: Point, q2: Point) -> bool {
+    let d1 = cross(q1, q2, p1);
+    let d2 = cross(q1, q2, p2);
+    let d3 = cross(p1, p2, q1);
+    let d4 = cross(p1, p2, q2);
+
+    if ((d1 > EPSILON && d2 < -EPSILON) || (d1 < -EPSILON && d2 > EPSILON))
+        && ((d3 > EPSILON && d4 < -EPSILON) || (d3 < -EPSILON && d4 > EPSILON))
+    {
+        return true;
+    }
+
+    // Touching or collinear cases: an endpoint lies on the other segment.
+    segment_distance(p1, q1, q2) <= EPSILON
+        || segment_distance(p2, q1, q2) <= EPSILON
+        || segment_distance(q1, p1, p2) <= EPSILON
+        || segment_distance(q2, p1, p2) <= EPSILON
+}
+
+fn polygon_contains(vertices: &[Point], p: Point) -> bool {
+    if edges(vertices).any(|(a, b)| segment_distance(p, a, b) <= EPSILON) {
+        return true;
+    }
+
+    // Even-odd ray casting towards +x.
+    edges(vertices)
+        .filter(|(a, b)| (a.y > p.y) != (b.y > p.y))
+        .filter(|(a, b)| p.x < a.x + (p.y - a.y) * (b.x - a.x) / (b.y - a.y))
+        .count()
+        % 2
+        == 1
+}
+
+fn polygons_overlap(a: &[Point], b: &[Point]) -> bool {
+    edges(a).any(|(p1, p2)| edges(b).any(|(q1, q2)| segments_intersect(p1, p2, q1, q2)))
+        || b.first().is_some_and(|&p| polygon_contains(a, p))
+        || a.first().is_some_and(|&p| polygon_contains(b, p))
+}
+
+/// Shapes that can describe their outline in their own local coordinates,
+/// centred on the origin unless they carry coordinates of their own.
+pub trait Geometry {
+    fn outline(&self) -> Outline;
+}
+
+impl<T: Geometry + ?Sized> Geometry for Box<T> {
+    fn outline(&self) -> Outline {
+        (**self).outline()
+    }
+}
+
+impl Geometry for Circle {
+    fn outline(&self) -> Outline {
+        Outline::Ellipse { center: Point::new(0.0, 0.0), a: self.radius, b: self.radius, rotation: 0.0 }
+    }
+}
+
+impl Geometry for Ellipse {
+    fn outline(&self) -> Outline {
+        Outline::Ellipse { center: Point::new(0.0, 0.0), a: self.semi_major, b: self.semi_minor, rotation: 0.0 }
+    }
+}
+
+fn centered_rectangle(width: f64, height: f64) -> Outline {
+    let (w, h) = (width / 2.0, height / 2.0);
+    Outline::Polygon(vec![Point::new(-w, -h), Point::new(w, -h), Point::new(w, h), Point::new(-w, h)])
+}
+
+impl Geometry for Square {
+    fn outline(&self) -> Outline {
+        centered_rectangle(self.side, self.side)
+    }
+}
+
+impl Geometry for Rectangle {
+    /// `width` runs along x and `length` along y.
+    fn outline(&self) -> Outline {
+        centered_rectangle(self.width, self.length)
+    }
+}
+
+impl Geometry for Triangle {
+    /// Side `c` lies along the x axis and the centroid sits at the origin.
+    fn outline(&self) -> Outline {
+        let (a, b, c) = (self.a, self.b, self.c);
+        let x = (b * b + c * c - a * a) / (2.0 * c);
+        let apex = Point::new(x, (b * b - x * x).max(0.0).sqrt());
+        let centroid = Point::new((c + apex.x) / 3.0, apex.y / 3.0);
+
+        Outline::Polygon(
+            [Point::new(0.0, 0.0), Point::new(c, 0.0), apex]
+                .iter()
+                .map(|p| p.offset(-centroid.x, -centroid.y))
+                .collect(),
+        )
+    }
+}
+
+impl Geometry for RegularPolygon {
+    /// Oriented with a flat bottom edge.
+    fn outline(&self) -> Outline {
+        let n = self.sides as f64;
+        let radius = self.side_length / (2.0 * (PI / n).sin());
+        let start = -PI / 2.0 + PI / n;
+
+        Outline::Polygon(
+            (0..self.sides)
+                .map(|k| {
+                    let angle = start + 2.0 * PI * k as f64 / n;
+                    Point::new(radius * angle.cos(), radius * angle.sin())
+                })
+                .collect(),
+        )
+    }
+}
+
+impl Geometry for Polygon {
+    /// Vertices are used exactly as given.
+    fn outline(&self) -> Outline {
+        Outline::Polygon(self.vertices.clone())
+    }
+}
+
+/// Uniform scale, then rotation, then translation.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Transform {
+    pub translation: Point,
+    /// Anticlockwise, in radians.
+    pub rotation: f64,
+    pub scale: f64,
+}
+
+impl Transform {
+    pub const IDENTITY: Transform = Transform { translation: Point { x: 0.0, y: 0.0 }, rotation: 0.0, scale: 1.0 };
+
+    pub fn apply(&self, p: Point) -> Point {
+        let scaled = Point::new(p.x * self.scale, p.y * self.scale);
+        rotate(scaled, self.rotation).offset(self.translation.x, self.translation.y)
+    }
+
+    pub fn apply_outline(&self, outline: &Outline) -> Outline {
+        match outline {
+            Outline::Ellipse { center, a, b, rotation } => Outline::Ellipse {
+                center: self.apply(*center),
+                a: a * self.scale,
+                b: b * self.scale,
+                rotation: rotation + self.rotation,
+            },
+            Outline::Polygon(vertices) => Outline::Polygon(vertices.iter().map(|&p| self.apply(p)).collect()),
+        }
+    }
+}
+
+impl Default for Transform {
+    fn default() -> Transform {
+        Transform::IDENTITY
+    }
+}
+
+/// A shape positioned in the plane.
+///
+/// Scaling and `rotate` act around the shape's own origin, so a placed circle
+/// stays where it is when it grows.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Placed<S> {
+    pub shape: S,
+    pub transform: Transform,
+}
+
+impl<S: Geometry> Placed<S> {
+    pub fn new(shape: S) -> Placed<S> {
+        Placed { shape, transform: Transform::IDENTITY }
+    }
+
+    pub fn at(shape: S, x: f64, y: f64) -> Placed<S> {
+        Placed::new(shape).translate(x, y)
+    }
+
+    pub fn translate(mut self, dx: f64, dy: f64) -> Placed<S> {
+        self.transform.translation = self.transform.translation.offset(dx, dy);
+        self
+    }
+
+    pub fn rotate(mut self, radians: f64) -> Placed<S> {
+        self.transform.rotation += radians;
+        self
+    }
+
+    /// Rotates the whole placement around `center`, moving the shape as well
+    /// as turning it.
+    pub fn rotate_about(mut self, center: Point, radians: f64) -> Placed<S> {
+        let t = self.transform.translation.offset(-center.x, -center.y);
+        self.transform.translation = rotate(t, radians).offset(center.x, center.y);
+        self.transform.rotation += radians;
+        self
+    }
+
+    pub fn scale(mut self, factor: f64) -> Result<Placed<S>, ShapeError> {
+        self.transform.scale *= check_dimension("scale factor", factor)?;
+        Ok(self)
+    }
+
+    /// The outline in world coordinates.
+    pub fn outline(&self) -> Outline {
+        self.transform.apply_outline(&self.shape.outline())
+    }
+
+    pub fn bounding_box(&self) -> Option<Aabb> {
+        self.outline().bounding_box()
+    }
+
+    pub fn contains(&self, p: Point) -> bool {
+        self.outline().contains(p)
+    }
+
+    pub fn overlaps<T: Geometry>(&self, other: &Placed<T>) -> bool {
+        self.outline().overlaps(&other.outline())
+    }
+}
+
+impl<S: Geometry> Geometry for Placed<S> {
+    fn outline(&self) -> Outline {
+        Placed::outline(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::f64::consts::FRAC_PI_4;
+
+    use super::*;
+    use crate::trait_example::Area;
+
+    fn aabb(min_x: f64, min_y: f64, max_x: f64, max_y: f64) -> Aabb {
+        Aabb { min: Point::new(min_x, min_y), max: Point::new(max_x, max_y) }
+    }
+
+    fn assert_aabb(actual: Aabb, expected: Aabb) {
+        assert!(actual.approx_eq(&expected), "expected {:?}, got {:?}", expected, actual);
+    }
+
+    #[test]
+    fn should_compute_bounding_boxes() {
+        let circle = Placed::at(Circle::new(2.0).unwrap(), 5.0, 5.0);
+        let rectangle = Placed::at(Rectangle::new(4.0, 2.0).unwrap(), 1.0, 0.0);
+        let ellipse = Placed::new(Ellipse::new(3.0, 1.0).unwrap()).rotate(PI / 2.0);
+        let diamond = Placed::new(Square::new(2.0).unwrap()).rotate(FRAC_PI_4);
+
+        assert_aabb(circle.bounding_box().unwrap(), aabb(3.0, 3.0, 7.0, 7.0));
+        assert_aabb(rectangle.bounding_box().unwrap(), aabb(-1.0, -1.0, 3.0, 1.0));
+        assert_aabb(ellipse.bounding_box().unwrap(), aabb(-1.0, -3.0, 1.0, 3.0));
+        let half_diagonal = 2.0_f64.sqrt();
+        assert_aabb(diamond.bounding_box().unwrap(), aabb(-half_diagonal, -half_diagonal, half_diagonal, half_diagonal));
+    }
+
+    #[test]
+    fn should_place_triangle_with_centroid_at_origin() {
+        let triangle = Triangle::new(3.0, 4.0, 5.0).unwrap();
+        let Outline::Polygon(vertices) = triangle.outline() else {
+            panic!("triangles are polygons");
+        };
+
+        let centroid = vertices.iter().fold((0.0, 0.0), |(x, y), p| (x + p.x / 3.0, y + p.y / 3.0));
+        let outlined = Polygon::new(vertices).unwrap();
+
+        assert!(approx_eq(centroid.0, 0.0) && approx_eq(centroid.1, 0.0));
+        assert!(approx_eq(outlined.area(), triangle.area()));
+    }
+
+    #[test]
+    fn should_test_point_containment_with_tolerance() {
+        let square = Placed::at(Square::new(2.0).unwrap(), 10.0, 0.0);
+        let diamond = Placed::new(Square::new(2.0).unwrap()).rotate(FRAC_PI_4);
+        let ellipse = Placed::new(Ellipse::new(2.0, 1.0).unwrap());
+
+        assert!(square.contains(Point::new(10.5, 0.5)));
+        assert!(square.contains(Point::new(11.0, 1.0 + EPSILON / 2.0)));
+        assert!(!square.contains(Point::new(11.1, 0.0)));
+        assert!(diamond.contains(Point::new(1.4, 0.0)));
+        assert!(!diamond.contains(Point::new(0.9, 0.9)));
+        assert!(ellipse.contains(Point::new(2.0, 0.0)));
+        assert!(!ellipse.contains(Point::new(0.0, 1.1)));
+    }
+
+    #[test]
+    fn should_detect_overlaps() {
+        let circle = Placed::new(Circle::new(1.0).unwrap());
+        let touching = Placed::at(Circle::new(1.0).unwrap(), 2.0, 0.0);
+        let apart = Placed::at(Circle::new(1.0).unwrap(), 2.1, 0.0);
+        let square = Placed::at(Square::new(2.0).unwrap(), 2.0, 2.0);
+        let corner = Placed::at(Square::new(2.0).unwrap(), 1.5, 1.5);
+
+        assert!(circle.overlaps(&touching));
+        assert!(!circle.overlaps(&apart));
+        // The square's corner at (1, 1) is outside the unit circle.
+        assert!(!circle.overlaps(&square));
+        assert!(circle.overlaps(&corner));
+    }
+
+    #[test]
+    fn should_detect_containment_as_overlap() {
+        let big = Placed::new(Square::new(10.0).unwrap());
+        let small = Placed::at(RegularPolygon::new(6, 1.0).unwrap(), 1.0, 1.0);
+        let ring = Placed::new(Circle::new(20.0).unwrap());
+
+        assert!(big.overlaps(&small));
+        assert!(small.overlaps(&big));
+        assert!(ring.overlaps(&big));
+    }
+
+    #[test]
+    fn should_overlap_rotated_ellipses() {
+        let horizontal = Placed::new(Ellipse::new(4.0, 1.0).unwrap());
+        let vertical = Placed::at(Ellipse::new(4.0, 1.0).unwrap(), 3.0, 3.0).rotate(PI / 2.0);
+        let far = Placed::at(Ellipse::new(4.0, 1.0).unwrap(), 5.2, 0.0).rotate(PI / 2.0);
+
+        assert!(horizontal.overlaps(&vertical));
+        assert!(!horizontal.overlaps(&far));
+    }
+
+    #[test]
+    fn should_compare_boxes_with_the_same_tolerance_as_approx_eq() {
+        let far = aabb(1e12, 1e12, 2e12, 2e12);
+        let edge = Point::new(2e12 + 1.0, 1.5e12);
+
+        assert!(approx_eq(edge.x, far.max.x));
+        assert!(far.contains(edge));
+        assert!(far.intersects(&aabb(edge.x, edge.y, 3e12, 3e12)));
+        assert!(!far.contains(Point::new(2e12 + 1e4, 1.5e12)));
+        assert!(!aabb(0.0, 0.0, 1.0, 1.0).contains(Point::new(1.0 + 1e-6, 0.5)));
+    }
+
+    #[test]
+    fn should_treat_empty_polygons_as_empty() {
+        let empty = Outline::Polygon(Vec::new());
+        let circle = Circle::new(1.0).unwrap().outline();
+
+        assert_eq!(empty.bounding_box(), None);
+        assert!(!empty.contains(Point::new(0.0, 0.0)));
+        assert!(!empty.overlaps(&circle));
+        assert!(!circle.overlaps(&empty));
+        assert!(!empty.overlaps(&empty));
+    }
+
+    #[test]
+    fn should_apply_transforms_in_order() {
+        let square = Placed::new(Square::new(2.0).unwrap()).scale(3.0).unwrap().translate(10.0, 0.0);
+        let orbit = Placed::at(Circle::new(1.0).unwrap(), 5.0, 0.0).rotate_about(Point::new(0.0, 0.0), PI / 2.0);
+
+        assert_aabb(square.bounding_box().unwrap(), aabb(7.0, -3.0, 13.0, 3.0));
+        assert_aabb(orbit.bounding_box().unwrap(), aabb(-1.0, 4.0, 1.0, 6.0));
+        assert!(Placed::new(Square::new(1.0).unwrap()).scale(-1.0).is_err());
+    }
+}
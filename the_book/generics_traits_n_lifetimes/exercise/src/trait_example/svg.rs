@@ -0,0 +1,309 @@
+//! Rendering shapes as SVG.
+//!
+//! Shapes use y-up coordinates with anticlockwise rotation, while SVG points y
+//! down, so everything is mirrored in the x axis on the way out. A shape that
+//! looks right on graph paper looks the same in the browser.
+
+use std::fmt::Write as _;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use super::geometry::{Aabb, Geometry, Outline, Placed};
+use super::Point;
+
+/// Pixels per unit when a document does not say otherwise.
+const DEFAULT_PIXELS_PER_UNIT: f64 = 20.0;
+
+/// Space left around the shapes, in scene units, so strokes are not clipped.
+const DEFAULT_MARGIN: f64 = 1.0;
+
+/// How a shape is painted. The stroke width is in pixels and does not grow
+/// when the scene is scaled.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    pub fill: String,
+    pub stroke: String,
+    pub stroke_width: f64,
+}
+
+impl Style {
+    pub fn filled(fill: &str) -> Style {
+        Style { fill: fill.to_owned(), ..Style::default() }
+    }
+}
+
+impl Default for Style {
+    fn default() -> Style {
+        Style { fill: "none".to_owned(), stroke: "black".to_owned(), stroke_width: 1.0 }
+    }
+}
+
+// Rounds away floating point noise such as 2.9999999999999996 and -0.
+fn number(value: f64) -> String {
+    let rounded = (value * 1e6).round() / 1e6;
+    if rounded == 0.0 { "0".to_owned() } else { rounded.to_string() }
+}
+
+fn escape(value: &str) -> String {
+    value.replace('&', "&amp;").replace('"', "&quot;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+fn paint(style: &Style) -> String {
+    format!(
+        r#"fill="{}" stroke="{}" stroke-width="{}" vector-effect="non-scaling-stroke""#,
+        escape(&style.fill),
+        escape(&style.stroke),
+        number(style.stroke_width)
+    )
+}
+
+/// Writes an outline as a single SVG element.
+pub fn outline_element(outline: &Outline, style: &Style) -> String {
+    match outline {
+        Outline::Ellipse { center, a, b, rotation } => {
+            let (cx, cy) = (number(center.x), number(-center.y));
+
+            if a == b {
+                format!(r#"<circle cx="{}" cy="{}" r="{}" {}/>"#, cx, cy, number(*a), paint(style))
+            } else {
+                let turn = match number(-rotation.to_degrees() % 180.0).as_str() {
+                    "0" => String::new(),
+                    degrees => format!(r#" transform="rotate({} {} {})""#, degrees, cx, cy),
+                };
+                format!(
+                    r#"<ellipse cx="{}" cy="{}" rx="{}" ry="{}"{} {}/>"#,
+                    cx,
+                    cy,
+                    number(*a),
+                    number(*b),
+                    turn,
+                    paint(style)
+                )
+            }
+        }
+        Outline::Polygon(vertices) => {
+            let points: Vec<String> = vertices.iter().map(|p| format!("{},{}", number(p.x), number(-p.y))).collect();
+            format!(r#"<polygon points="{}" {}/>"#, points.join(" "), paint(style))
+        }
+    }
+}
+
+fn document(bounds: Aabb, margin: f64, pixels_per_unit: f64, elements: &[String]) -> String {
+    let view_box = Aabb { min: bounds.min.offset(-margin, -margin), max: bounds.max.offset(margin, margin) };
+    let mut out = String::new();
+
+    let _ = writeln!(
+        out,
+        r#"<svg xmlns="http://www.w3.org/2000/svg" width="{}" height="{}" viewBox="{} {} {} {}">"#,
+        number(view_box.width() * pixels_per_unit),
+        number(view_box.height() * pixels_per_unit),
+        number(view_box.min.x),
+        number(-view_box.max.y),
+        number(view_box.width()),
+        number(view_box.height())
+    );
+    for element in elements {
+        let _ = writeln!(out, "  {}", element);
+    }
+    out.push_str("</svg>\n");
+
+    out
+}
+
+/// Anything with an outline can draw itself.
+pub trait ToSvg {
+    /// The shape as one element, in world coordinates.
+    fn to_svg_element(&self, style: &Style) -> String;
+
+    /// A standalone document holding just this shape, with a margin around
+    /// its bounding box.
+    fn to_svg(&self) -> String;
+}
+
+impl<T: Geometry + ?Sized> ToSvg for T {
+    fn to_svg_element(&self, style: &Style) -> String {
+        outline_element(&self.outline(), style)
+    }
+
+    fn to_svg(&self) -> String {
+        let outline = self.outline();
+        let element = outline_element(&outline, &Style::default());
+        let origin = Point::new(0.0, 0.0);
+        let bounds = outline.bounding_box().unwrap_or(Aabb { min: origin, max: origin });
+        document(bounds, DEFAULT_MARGIN, DEFAULT_PIXELS_PER_UNIT, &[element])
+    }
+}
+
+/// Several placed shapes drawn into one document, in the order they were
+/// added, so later shapes paint over earlier ones.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Scene<S> {
+    shapes: Vec<(Placed<S>, Style)>,
+    margin: f64,
+    pixels_per_unit: f64,
+}
+
+impl<S: Geometry> Scene<S> {
+    pub fn new() -> Scene<S> {
+        Scene { shapes: Vec::new(), margin: DEFAULT_MARGIN, pixels_per_unit: DEFAULT_PIXELS_PER_UNIT }
+    }
+
+    /// Space left around the shapes, in scene units.
+    pub fn margin(mut self, margin: f64) -> Scene<S> {
+        self.margin = margin.max(0.0);
+        self
+    }
+
+    pub fn pixels_per_unit(mut self, pixels_per_unit: f64) -> Scene<S> {
+        self.pixels_per_unit = pixels_per_unit;
+        self
+    }
+
+    pub fn add(&mut self, shape: Placed<S>) -> &mut Scene<S> {
+        self.add_styled(shape, Style::default())
+    }
+
+    pub fn add_styled(&mut self, shape: Placed<S>, style: Style) -> &mut Scene<S> {
+        self.shapes.push((shape, style));
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.shapes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.shapes.is_empty()
+    }
+
+    /// The bounds of every shape, before the margin is added. An empty scene,
+    /// or one of polygons without vertices, is a single point at the origin.
+    pub fn bounds(&self) -> Aabb {
+        let origin = Point::new(0.0, 0.0);
+
+        self.shapes
+            .iter()
+            .filter_map(|(shape, _)| shape.bounding_box())
+            .reduce(|a, b| a.union(&b))
+            .unwrap_or(Aabb { min: origin, max: origin })
+    }
+
+    pub fn to_svg(&self) -> String {
+        let elements: Vec<String> = self.shapes.iter().map(|(shape, style)| shape.to_svg_element(style)).collect();
+        document(self.bounds(), self.margin, self.pixels_per_unit, &elements)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.to_svg())
+    }
+}
+
+impl<S: Geometry> Default for Scene<S> {
+    fn default() -> Scene<S> {
+        Scene::new()
+    }
+}
+
+impl<S: Geometry> FromIterator<Placed<S>> for Scene<S> {
+    fn from_iter<I: IntoIterator<Item = Placed<S>>>(iter: I) -> Scene<S> {
+        let mut scene = Scene::new();
+        iter.into_iter().for_each(|shape| {
+            scene.add(shape);
+        });
+        scene
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::env;
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::trait_example::description::parse_scene;
+    use crate::trait_example::{AnyShape, Circle, Ellipse, Square};
+
+    fn golden_path(name: &str) -> PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(name)
+    }
+
+    // Set UPDATE_GOLDEN=1 to rewrite the files after an intended change.
+    fn assert_golden(name: &str, actual: &str) {
+        let path = golden_path(name);
+
+        if env::var_os("UPDATE_GOLDEN").is_some() {
+            fs::write(&path, actual).unwrap();
+        }
+        let expected = fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+        assert_eq!(actual, expected, "output differs from {}", path.display());
+    }
+
+    #[test]
+    fn should_render_single_shape_document() {
+        assert_golden("circle.svg", &Circle::new(2.5).unwrap().to_svg());
+    }
+
+    #[test]
+    fn should_render_scene_from_description() {
+        let shapes = parse_scene(
+            "\
+circle r=3
+rect w=2 l=5 at=6,0
+square s=1.5 rot=45 at=0,5
+triangle a=3 b=4 c=5 at=-6,0
+ellipse a=4 b=2 rot=30 at=0,-6
+ngon n=6 s=1 at=6,5
+polygon 0,0 4,0 4,3 at=-8,-8
+",
+        )
+        .unwrap();
+        let mut scene: Scene<AnyShape> = shapes.into_iter().collect();
+        scene.add_styled(Placed::at(Circle::new(0.5).unwrap().into(), 0.0, 0.0), Style::filled("#e33"));
+
+        assert_golden("scene.svg", &scene.to_svg());
+    }
+
+    #[test]
+    fn should_flip_y_axis() {
+        let high = Placed::at(Square::new(2.0).unwrap(), 0.0, 10.0);
+        let tilted = Placed::new(Ellipse::new(2.0, 1.0).unwrap()).rotate(std::f64::consts::FRAC_PI_2);
+
+        assert_eq!(
+            high.to_svg_element(&Style::default()),
+            r#"<polygon points="-1,-9 1,-9 1,-11 -1,-11" fill="none" stroke="black" stroke-width="1" vector-effect="non-scaling-stroke"/>"#
+        );
+        assert!(tilted.to_svg_element(&Style::default()).contains(r#"transform="rotate(-90 0 0)""#));
+    }
+
+    #[test]
+    fn should_frame_scene_with_margin() {
+        let mut scene = Scene::new().margin(0.5).pixels_per_unit(10.0);
+        scene.add(Placed::at(Square::new(2.0).unwrap(), 3.0, 1.0));
+
+        let bounds = scene.bounds();
+
+        assert_eq!((bounds.min, bounds.max), (Point::new(2.0, 0.0), Point::new(4.0, 2.0)));
+        assert!(scene.to_svg().starts_with(r#"<svg xmlns="http://www.w3.org/2000/svg" width="30" height="30" viewBox="1.5 -2.5 3 3">"#));
+        assert!(Scene::<Square>::new().to_svg().contains(r#"viewBox="-1 -1 2 2""#));
+    }
+
+    #[test]
+    fn should_escape_style_attributes() {
+        let style = Style { fill: r##"url("#a")"##.to_owned(), ..Style::default() };
+
+        assert!(Circle::new(1.0).unwrap().to_svg_element(&style).contains(r##"fill="url(&quot;#a&quot;)""##));
+    }
+
+    #[test]
+    fn should_save_scene_to_file() {
+        let path = env::temp_dir().join(format!("{}-scene.svg", std::process::id()));
+        let scene: Scene<Circle> = [Placed::new(Circle::new(1.0).unwrap())].into_iter().collect();
+
+        scene.save(&path).unwrap();
+        let written = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(written, scene.to_svg());
+    }
+}
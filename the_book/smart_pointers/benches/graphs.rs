@@ -0,0 +1,42 @@
+//! Breadth-first traversal of the same graph built from `Rc` nodes and in
+//! an arena.
+//!
+//! The graph is a grid where every node points right and down, so most
+//! nodes have two parents and the traversals have to skip repeats.
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use smart_pointers::arena_graph::{ArenaGraph, NodeId};
+use smart_pointers::reference_counting::Graph;
+
+fn grid(side: usize) -> Graph {
+    let nodes: Vec<Graph> = (0..side * side).map(|i| Graph::new(i as u32)).collect();
+    for row in 0..side {
+        for column in 0..side {
+            let node = &nodes[row * side + column];
+            if column + 1 < side {
+                node.add_child(&nodes[row * side + column + 1]).unwrap();
+            }
+            if row + 1 < side {
+                node.add_child(&nodes[(row + 1) * side + column]).unwrap();
+            }
+        }
+    }
+    nodes[0].clone()
+}
+
+fn traverse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("bfs");
+    for side in [30, 100] {
+        let rc = grid(side);
+        let arena = ArenaGraph::from_rc(&rc);
+
+        group.bench_with_input(BenchmarkId::new("Rc", side), &rc, |b, rc| b.iter(|| rc.bfs().count()));
+        group.bench_with_input(BenchmarkId::new("arena", side), &arena, |b, arena| {
+            b.iter(|| arena.bfs_paths(NodeId(0)).distance(NodeId(arena.len() - 1)))
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, traverse);
+criterion_main!(benches);
@@ -0,0 +1,59 @@
+//! Insert and lookup costs of the plain and balanced trees.
+//!
+//! Sorted input turns `BinaryTree` into a linked list, so it is benchmarked
+//! with fewer keys than random input to keep a run short.
+
+use std::hint::black_box;
+
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion};
+use smart_pointers::avl_tree::AvlTree;
+use smart_pointers::binary_tree::BinaryTree;
+
+// The same pseudo-random keys on every run, so results stay comparable.
+fn random_keys(len: usize) -> Vec<u32> {
+    let mut state: u64 = 0x2545_f491_4f6c_dd1d;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            state as u32
+        })
+        .collect()
+}
+
+fn insert(c: &mut Criterion) {
+    let inputs = [("sorted", (0..2_000).collect::<Vec<u32>>()), ("random", random_keys(20_000))];
+
+    let mut group = c.benchmark_group("insert");
+    for (name, keys) in &inputs {
+        group.bench_with_input(BenchmarkId::new("BinaryTree", name), keys, |b, keys| {
+            b.iter(|| keys.iter().copied().collect::<BinaryTree<u32>>())
+        });
+        group.bench_with_input(BenchmarkId::new("AvlTree", name), keys, |b, keys| {
+            b.iter(|| keys.iter().copied().collect::<AvlTree<u32>>())
+        });
+    }
+    group.finish();
+}
+
+fn lookup(c: &mut Criterion) {
+    let inputs = [("sorted", (0..2_000).collect::<Vec<u32>>()), ("random", random_keys(20_000))];
+
+    let mut group = c.benchmark_group("contains");
+    for (name, keys) in &inputs {
+        let plain: BinaryTree<u32> = keys.iter().copied().collect();
+        let balanced: AvlTree<u32> = keys.iter().copied().collect();
+
+        group.bench_with_input(BenchmarkId::new("BinaryTree", name), keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|key| plain.contains(black_box(*key))).count())
+        });
+        group.bench_with_input(BenchmarkId::new("AvlTree", name), keys, |b, keys| {
+            b.iter(|| keys.iter().filter(|key| balanced.contains(black_box(*key))).count())
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, insert, lookup);
+criterion_main!(benches);
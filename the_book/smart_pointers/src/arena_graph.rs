@@ -0,0 +1,175 @@
+//! A directed graph stored in two vectors instead of a web of `Rc`s.
+//!
+//! Nodes are addressed by [`NodeId`], an index into the node vector, and
+//! each node keeps its outgoing edges in a vector of its own. There is no
+//! reference counting or `RefCell` borrow checking on every step, cycles are
+//! allowed, and the whole graph is freed in one go. The price is that nodes
+//! cannot be removed, since that would shift the ids of the others.
+//!
+//! The algorithms live in [`algorithms`].
+
+use std::collections::HashMap;
+use std::fmt;
+
+use crate::reference_counting::Graph;
+
+pub mod algorithms;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NodeId(pub usize);
+
+impl fmt::Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// Nodes holding a `T`, joined by edges weighted with a `W`. Unweighted
+/// graphs use `W = ()`.
+#[derive(Debug, Clone)]
+pub struct ArenaGraph<T, W = ()> {
+    nodes: Vec<T>,
+    edges: Vec<Vec<(NodeId, W)>>,
+}
+
+impl<T, W> ArenaGraph<T, W> {
+    pub fn new() -> ArenaGraph<T, W> {
+        ArenaGraph { nodes: Vec::new(), edges: Vec::new() }
+    }
+
+    pub fn with_capacity(nodes: usize) -> ArenaGraph<T, W> {
+        ArenaGraph { nodes: Vec::with_capacity(nodes), edges: Vec::with_capacity(nodes) }
+    }
+
+    pub fn add_node(&mut self, value: T) -> NodeId {
+        self.nodes.push(value);
+        self.edges.push(Vec::new());
+        NodeId(self.nodes.len() - 1)
+    }
+
+    /// Adds an edge from `from` to `to`. Parallel edges and self loops are
+    /// allowed.
+    ///
+    /// Panics if either node does not exist.
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: W) {
+        assert!(from.0 < self.nodes.len(), "no node {}", from);
+        assert!(to.0 < self.nodes.len(), "no node {}", to);
+        self.edges[from.0].push((to, weight));
+    }
+
+    /// Removes the first edge from `from` to `to`, returning its weight.
+    pub fn remove_edge(&mut self, from: NodeId, to: NodeId) -> Option<W> {
+        let edges = self.edges.get_mut(from.0)?;
+        let index = edges.iter().position(|(target, _)| *target == to)?;
+        Some(edges.remove(index).1)
+    }
+
+    pub fn node(&self, id: NodeId) -> Option<&T> {
+        self.nodes.get(id.0)
+    }
+
+    pub fn node_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.nodes.get_mut(id.0)
+    }
+
+    /// The targets and weights of the edges leaving `id`.
+    ///
+    /// Panics if the node does not exist.
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, W)] {
+        &self.edges[id.0]
+    }
+
+    pub fn neighbors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges(id).iter().map(|(to, _)| *to)
+    }
+
+    pub fn node_ids(&self) -> impl Iterator<Item = NodeId> + use<T, W> {
+        (0..self.nodes.len()).map(NodeId)
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn edge_count(&self) -> usize {
+        self.edges.iter().map(Vec::len).sum()
+    }
+}
+
+impl<T: Clone> ArenaGraph<T, ()> {
+    /// Copies everything reachable from `root` into an arena. Nodes are
+    /// numbered in depth-first order, so `root` becomes `NodeId(0)`, and a
+    /// node shared by several parents is copied once.
+    pub fn from_rc(root: &Graph<T>) -> ArenaGraph<T, ()> {
+        let nodes: Vec<Graph<T>> = root.dfs().collect();
+        let mut graph = ArenaGraph::with_capacity(nodes.len());
+        let ids: HashMap<usize, NodeId> = nodes.iter().map(|node| (node.id(), graph.add_node(node.value().clone()))).collect();
+
+        for (from, node) in nodes.iter().enumerate() {
+            for child in node.children() {
+                graph.add_edge(NodeId(from), ids[&child.id()], ());
+            }
+        }
+        graph
+    }
+}
+
+impl<T, W> Default for ArenaGraph<T, W> {
+    fn default() -> ArenaGraph<T, W> {
+        ArenaGraph::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_add_and_remove_edges() {
+        let mut graph = ArenaGraph::new();
+        let a = graph.add_node("a");
+        let b = graph.add_node("b");
+        graph.add_edge(a, b, 3);
+        graph.add_edge(a, b, 5);
+        graph.add_edge(b, b, 1);
+
+        assert_eq!(graph.len(), 2);
+        assert_eq!(graph.edge_count(), 3);
+        assert_eq!(graph.remove_edge(a, b), Some(3));
+        assert_eq!(graph.remove_edge(b, a), None);
+        assert_eq!(graph.edges(a), &[(b, 5)]);
+        assert_eq!(graph.neighbors(b).collect::<Vec<_>>(), vec![b]);
+        *graph.node_mut(a).unwrap() = "z";
+        assert_eq!(graph.node(a), Some(&"z"));
+        assert_eq!(graph.node(NodeId(2)), None);
+    }
+
+    #[test]
+    #[should_panic(expected = "no node 1")]
+    fn should_refuse_edges_from_missing_nodes() {
+        let mut graph = ArenaGraph::new();
+        let a = graph.add_node("a");
+
+        graph.add_edge(NodeId(1), a, ());
+    }
+
+    #[test]
+    fn should_copy_shared_rc_nodes_once() {
+        let root = Graph::new('r');
+        let (left, right, shared) = (Graph::new('l'), Graph::new('x'), Graph::new('s'));
+        root.add_child(&left).unwrap();
+        root.add_child(&right).unwrap();
+        left.add_child(&shared).unwrap();
+        right.add_child(&shared).unwrap();
+
+        let graph = ArenaGraph::from_rc(&root);
+
+        assert_eq!(graph.node_ids().map(|id| *graph.node(id).unwrap()).collect::<String>(), "rlsx");
+        assert_eq!(graph.edge_count(), 4);
+        assert_eq!(graph.neighbors(NodeId(3)).collect::<Vec<_>>(), vec![NodeId(2)]);
+    }
+}
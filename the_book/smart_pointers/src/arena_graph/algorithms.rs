@@ -0,0 +1,310 @@
+//! Graph algorithms over an [`ArenaGraph`].
+//!
+//! All of them loop over explicit stacks and queues rather than recursing,
+//! so long paths do not overflow the call stack.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::ops::Add;
+
+use super::{ArenaGraph, NodeId};
+
+/// A cycle found while sorting, as the path that closes it. The first node
+/// is repeated at the end, so a self loop on `3` is `[3, 3]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CycleError {
+    pub path: Vec<NodeId>,
+}
+
+impl fmt::Display for CycleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "graph has a cycle: ")?;
+        for (i, id) in self.path.iter().enumerate() {
+            if i > 0 {
+                write!(f, " -> ")?;
+            }
+            write!(f, "{}", id)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for CycleError {}
+
+/// Distances from one source node and the paths that achieve them.
+#[derive(Debug, Clone)]
+pub struct ShortestPaths<D> {
+    source: NodeId,
+    distances: Vec<Option<D>>,
+    previous: Vec<Option<NodeId>>,
+}
+
+impl<D: Copy> ShortestPaths<D> {
+    pub fn source(&self) -> NodeId {
+        self.source
+    }
+
+    /// `None` if `to` cannot be reached.
+    pub fn distance(&self, to: NodeId) -> Option<D> {
+        self.distances.get(to.0).copied().flatten()
+    }
+
+    /// The nodes from the source to `to`, both included.
+    pub fn path_to(&self, to: NodeId) -> Option<Vec<NodeId>> {
+        self.distance(to)?;
+
+        let mut path = vec![to];
+        let mut current = to;
+        while let Some(previous) = self.previous[current.0] {
+            path.push(previous);
+            current = previous;
+        }
+        path.reverse();
+        Some(path)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mark {
+    Unvisited,
+    OnPath,
+    Done,
+}
+
+impl<T, W> ArenaGraph<T, W> {
+    // Depth-first search from every node, returning nodes in post-order or
+    // the first cycle it runs into.
+    fn post_order(&self) -> Result<Vec<NodeId>, CycleError> {
+        let mut marks = vec![Mark::Unvisited; self.len()];
+        let mut order = Vec::with_capacity(self.len());
+        // The current path, with how many of each node's edges are explored.
+        let mut path: Vec<(NodeId, usize)> = Vec::new();
+
+        for start in self.node_ids() {
+            if marks[start.0] != Mark::Unvisited {
+                continue;
+            }
+            marks[start.0] = Mark::OnPath;
+            path.push((start, 0));
+
+            while let Some((node, explored)) = path.last_mut() {
+                let Some(&(next, _)) = self.edges(*node).get(*explored) else {
+                    marks[node.0] = Mark::Done;
+                    order.push(*node);
+                    path.pop();
+                    continue;
+                };
+                *explored += 1;
+
+                match marks[next.0] {
+                    Mark::Unvisited => {
+                        marks[next.0] = Mark::OnPath;
+                        path.push((next, 0));
+                    }
+                    Mark::OnPath => {
+                        let from = path.iter().position(|(id, _)| *id == next).unwrap();
+                        let mut cycle: Vec<NodeId> = path[from..].iter().map(|(id, _)| *id).collect();
+                        cycle.push(next);
+                        return Err(CycleError { path: cycle });
+                    }
+                    Mark::Done => {}
+                }
+            }
+        }
+
+        Ok(order)
+    }
+
+    /// Orders the nodes so every edge points forward, or reports a cycle.
+    pub fn topological_sort(&self) -> Result<Vec<NodeId>, CycleError> {
+        let mut order = self.post_order()?;
+        order.reverse();
+        Ok(order)
+    }
+
+    /// A cycle in the graph, if there is one.
+    pub fn find_cycle(&self) -> Option<Vec<NodeId>> {
+        self.post_order().err().map(|cycle| cycle.path)
+    }
+
+    /// Shortest paths from `source` counted in edges, ignoring weights.
+    ///
+    /// Panics if `source` does not exist.
+    pub fn bfs_paths(&self, source: NodeId) -> ShortestPaths<usize> {
+        let mut paths = ShortestPaths { source, distances: vec![None; self.len()], previous: vec![None; self.len()] };
+        paths.distances[source.0] = Some(0);
+        let mut queue = VecDeque::from([source]);
+
+        while let Some(node) = queue.pop_front() {
+            let distance = paths.distances[node.0].unwrap() + 1;
+            for next in self.neighbors(node) {
+                if paths.distances[next.0].is_none() {
+                    paths.distances[next.0] = Some(distance);
+                    paths.previous[next.0] = Some(node);
+                    queue.push_back(next);
+                }
+            }
+        }
+
+        paths
+    }
+
+    /// Groups nodes joined by edges in either direction. Each component is
+    /// sorted, and components are ordered by their smallest node.
+    pub fn connected_components(&self) -> Vec<Vec<NodeId>> {
+        let mut parent: Vec<usize> = (0..self.len()).collect();
+
+        fn root(parent: &mut [usize], mut node: usize) -> usize {
+            while parent[node] != node {
+                parent[node] = parent[parent[node]];
+                node = parent[node];
+            }
+            node
+        }
+
+        for from in self.node_ids() {
+            for to in self.neighbors(from) {
+                let (a, b) = (root(&mut parent, from.0), root(&mut parent, to.0));
+                // The smaller index wins, so it names the component.
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut components: Vec<Vec<NodeId>> = Vec::new();
+        let mut component_of = vec![usize::MAX; self.len()];
+        for node in self.node_ids() {
+            let root = root(&mut parent, node.0);
+            if component_of[root] == usize::MAX {
+                component_of[root] = components.len();
+                components.push(Vec::new());
+            }
+            components[component_of[root]].push(node);
+        }
+        components
+    }
+}
+
+impl<T, W> ArenaGraph<T, W>
+where
+    W: Copy + Ord + Add<Output = W> + Default,
+{
+    /// Shortest paths from `source` by total weight, with Dijkstra's
+    /// algorithm. `W::default()` is taken as zero, and weights must not be
+    /// negative.
+    ///
+    /// Panics if `source` does not exist. Path lengths are summed with `+`,
+    /// so a path whose total does not fit in `W` panics in debug builds and
+    /// wraps in release builds, for the integer types.
+    pub fn dijkstra(&self, source: NodeId) -> ShortestPaths<W> {
+        let mut paths = ShortestPaths { source, distances: vec![None; self.len()], previous: vec![None; self.len()] };
+        paths.distances[source.0] = Some(W::default());
+        let mut queue = BinaryHeap::from([Reverse((W::default(), source))]);
+
+        while let Some(Reverse((distance, node))) = queue.pop() {
+            // A stale entry for a node that was reached more cheaply since.
+            if paths.distances[node.0].is_some_and(|best| distance > best) {
+                continue;
+            }
+            for &(next, weight) in self.edges(node) {
+                let candidate = distance + weight;
+                if paths.distances[next.0].is_none_or(|best| candidate < best) {
+                    paths.distances[next.0] = Some(candidate);
+                    paths.previous[next.0] = Some(node);
+                    queue.push(Reverse((candidate, next)));
+                }
+            }
+        }
+
+        paths
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ids(ids: &[usize]) -> Vec<NodeId> {
+        ids.iter().map(|&id| NodeId(id)).collect()
+    }
+
+    fn graph(len: usize, edges: &[(usize, usize, u32)]) -> ArenaGraph<(), u32> {
+        let mut graph = ArenaGraph::with_capacity(len);
+        for _ in 0..len {
+            graph.add_node(());
+        }
+        for &(from, to, weight) in edges {
+            graph.add_edge(NodeId(from), NodeId(to), weight);
+        }
+        graph
+    }
+
+    #[test]
+    fn should_sort_topologically() {
+        // Getting dressed: 0 socks, 1 shoes, 2 trousers, 3 belt, 4 shirt.
+        let graph = graph(5, &[(0, 1), (2, 1), (2, 3), (4, 3)].map(|(a, b)| (a, b, 1)));
+
+        let order = graph.topological_sort().unwrap();
+
+        assert_eq!(order.len(), 5);
+        let place = |id: usize| order.iter().position(|n| n.0 == id).unwrap();
+        for from in graph.node_ids() {
+            assert!(graph.neighbors(from).all(|to| place(from.0) < place(to.0)));
+        }
+    }
+
+    #[test]
+    fn should_report_the_cycle_path() {
+        let looped = graph(5, &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (3, 1, 1), (3, 4, 1)]);
+        let self_loop = graph(2, &[(0, 1, 1), (1, 1, 1)]);
+
+        let err = looped.topological_sort().unwrap_err();
+
+        assert_eq!(err.path, ids(&[1, 2, 3, 1]));
+        assert_eq!(err.to_string(), "graph has a cycle: 1 -> 2 -> 3 -> 1");
+        assert_eq!(self_loop.find_cycle(), Some(ids(&[1, 1])));
+        assert_eq!(graph(3, &[(0, 1, 1), (0, 2, 1), (1, 2, 1)]).find_cycle(), None);
+    }
+
+    #[test]
+    fn should_find_fewest_hops_and_cheapest_paths() {
+        //  0 --1--> 1 --1--> 2 --1--> 3
+        //  |                          ^
+        //  +------------10------------+
+        let graph = graph(5, &[(0, 1, 1), (1, 2, 1), (2, 3, 1), (0, 3, 10)]);
+
+        let hops = graph.bfs_paths(NodeId(0));
+        let cost = graph.dijkstra(NodeId(0));
+
+        assert_eq!(hops.path_to(NodeId(3)), Some(ids(&[0, 3])));
+        assert_eq!(hops.distance(NodeId(3)), Some(1));
+        assert_eq!(cost.path_to(NodeId(3)), Some(ids(&[0, 1, 2, 3])));
+        assert_eq!(cost.distance(NodeId(3)), Some(3));
+        assert_eq!(cost.path_to(NodeId(0)), Some(ids(&[0])));
+        assert_eq!(cost.distance(NodeId(4)), None);
+        assert_eq!(hops.path_to(NodeId(4)), None);
+    }
+
+    #[test]
+    fn should_group_connected_components() {
+        let graph = graph(7, &[(1, 0, 1), (2, 1, 1), (5, 3, 1), (6, 6, 1)]);
+
+        assert_eq!(graph.connected_components(), vec![ids(&[0, 1, 2]), ids(&[3, 5]), ids(&[4]), ids(&[6])]);
+        assert!(ArenaGraph::<(), ()>::new().connected_components().is_empty());
+    }
+
+    #[test]
+    fn should_handle_long_paths_without_recursion() {
+        let len = 200_000;
+        let chain: Vec<(usize, usize, u32)> = (1..len).map(|to| (to - 1, to, 2)).collect();
+        let mut graph = graph(len, &chain);
+
+        assert_eq!(graph.topological_sort().unwrap(), ids(&(0..len).collect::<Vec<_>>()));
+        assert_eq!(graph.dijkstra(NodeId(0)).distance(NodeId(len - 1)), Some(2 * (len as u32 - 1)));
+        assert_eq!(graph.connected_components().len(), 1);
+
+        graph.add_edge(NodeId(len - 1), NodeId(0), 1);
+        assert_eq!(graph.find_cycle().map(|path| path.len()), Some(len + 1));
+    }
+}
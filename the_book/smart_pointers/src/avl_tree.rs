@@ -0,0 +1,876 @@
+//! An AVL tree with the same interface as [`BinaryTree`](crate::binary_tree::BinaryTree).
+//!
+//! Nodes live in a `Vec` and link to each other by index, which lets insert
+//! and remove walk back up the path they came down without parent pointers
+//! or recursion. Every subtree's heights differ by at most one, so the tree
+//! stays within about 1.44 log2(n) levels whatever order keys arrive in.
+
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
+
+use crate::binary_tree::Duplicates;
+
+#[derive(Debug, Clone)]
+struct Node<K, V> {
+    key: K,
+    value: V,
+    count: usize,
+    height: usize,
+    left: Option<usize>,
+    right: Option<usize>,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(key: K, value: V) -> Node<K, V> {
+        Node { key, value, count: 1, height: 1, left: None, right: None }
+    }
+}
+
+/// A self-balancing binary search tree mapping ordered keys to values. With
+/// the default `V = ()` it is a set.
+#[derive(Debug, Clone)]
+pub struct AvlTree<K, V = ()> {
+    nodes: Vec<Node<K, V>>,
+    root: Option<usize>,
+    duplicates: Duplicates,
+}
+
+impl<K, V> AvlTree<K, V> {
+    fn height_of(&self, link: Option<usize>) -> usize {
+        link.map_or(0, |i| self.nodes[i].height)
+    }
+
+    fn update_height(&mut self, i: usize) {
+        let node = &self.nodes[i];
+        self.nodes[i].height = 1 + self.height_of(node.left).max(self.height_of(node.right));
+    }
+
+    fn balance_factor(&self, i: usize) -> isize {
+        let node = &self.nodes[i];
+        self.height_of(node.left) as isize - self.height_of(node.right) as isize
+    }
+
+    // Returns the new root of the subtree that was rooted at `i`.
+    fn rotate_right(&mut self, i: usize) -> usize {
+        let left = self.nodes[i].left.expect("rotate_right needs a left child");
+        self.nodes[i].left = self.nodes[left].right;
+        self.nodes[left].right = Some(i);
+        self.update_height(i);
+        self.update_height(left);
+        left
+    }
+
+    fn rotate_left(&mut self, i: usize) -> usize {
+        let right = self.nodes[i].right.expect("rotate_left needs a right child");
+        self.nodes[i].right = self.nodes[right].left;
+        self.nodes[right].left = Some(i);
+        self.update_height(i);
+        self.update_height(right);
+        right
+    }
+
+    fn rebalance(&mut self, i: usize) -> usize {
+        self.update_height(i);
+
+        match self.balance_factor(i) {
+            2.. => {
+                let left = self.nodes[i].left.expect("left-heavy nodes have a left child");
+                if self.balance_factor(left) < 0 {
+                    self.nodes[i].left = Some(self.rotate_left(left));
+                }
+                self.rotate_right(i)
+            }
+            ..=-2 => {
+                let right = self.nodes[i].right.expect("right-heavy nodes have a right child");
+                if self.balance_factor(right) > 0 {
+                    self.nodes[i].right = Some(self.rotate_right(right));
+                }
+                self.rotate_left(i)
+            }
+            _ => i,
+        }
+    }
+
+    // Points whatever linked to `old` at `new` instead.
+    fn replace_child(&mut self, parent: Option<usize>, old: usize, new: Option<usize>) {
+        match parent {
+            None => self.root = new,
+            Some(p) if self.nodes[p].left == Some(old) => self.nodes[p].left = new,
+            Some(p) => self.nodes[p].right = new,
+        }
+    }
+
+    fn swap_entries(&mut self, i: usize, j: usize) {
+        let (low, high) = (i.min(j), i.max(j));
+        let (front, back) = self.nodes.split_at_mut(high);
+        let (a, b) = (&mut front[low], &mut back[0]);
+
+        mem::swap(&mut a.key, &mut b.key);
+        mem::swap(&mut a.value, &mut b.value);
+        mem::swap(&mut a.count, &mut b.count);
+    }
+
+    /// Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn duplicates(&self) -> Duplicates {
+        self.duplicates
+    }
+
+    /// Number of nodes on the longest path from the root to a leaf; zero
+    /// for an empty tree.
+    pub fn height(&self) -> usize {
+        self.height_of(self.root)
+    }
+
+    /// Entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { nodes: &self.nodes, stack: Vec::new() };
+        iter.push_left_spine(self.root);
+        iter
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn pre_order(&self) -> PreOrder<'_, K, V> {
+        PreOrder { nodes: &self.nodes, stack: self.root.into_iter().collect() }
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, K, V> {
+        PostOrder { nodes: &self.nodes, stack: self.root.map(|i| (i, false)).into_iter().collect() }
+    }
+
+    /// Entries a level at a time from the root down, left to right.
+    pub fn level_order(&self) -> LevelOrder<'_, K, V> {
+        LevelOrder { nodes: &self.nodes, queue: self.root.into_iter().collect() }
+    }
+}
+
+impl<K: Ord, V> AvlTree<K, V> {
+    pub fn new() -> AvlTree<K, V> {
+        AvlTree::with_duplicates(Duplicates::Replace)
+    }
+
+    pub fn with_duplicates(duplicates: Duplicates) -> AvlTree<K, V> {
+        AvlTree { nodes: Vec::new(), root: None, duplicates }
+    }
+
+    // The nodes from the root down to `key`, and whether the last of them
+    // holds it. If not, the last is where it would be attached.
+    fn search<Q: Ord + ?Sized>(&self, key: &Q) -> (Vec<usize>, bool)
+    where
+        K: Borrow<Q>,
+    {
+        let mut path = Vec::with_capacity(self.height());
+        let mut link = self.root;
+
+        while let Some(i) = link {
+            path.push(i);
+            link = match key.cmp(self.nodes[i].key.borrow()) {
+                Ordering::Less => self.nodes[i].left,
+                Ordering::Greater => self.nodes[i].right,
+                Ordering::Equal => return (path, true),
+            };
+        }
+
+        (path, false)
+    }
+
+    fn find<Q: Ord + ?Sized>(&self, key: &Q) -> Option<usize>
+    where
+        K: Borrow<Q>,
+    {
+        let mut link = self.root;
+
+        while let Some(i) = link {
+            link = match key.cmp(self.nodes[i].key.borrow()) {
+                Ordering::Less => self.nodes[i].left,
+                Ordering::Greater => self.nodes[i].right,
+                Ordering::Equal => return Some(i),
+            };
+        }
+
+        None
+    }
+
+    // Walks back up `path`, root first, restoring heights and balance.
+    fn rebalance_path(&mut self, path: &[usize]) {
+        for (depth, &i) in path.iter().enumerate().rev() {
+            let top = self.rebalance(i);
+            if top != i {
+                let parent = depth.checked_sub(1).map(|d| path[d]);
+                self.replace_child(parent, i, Some(top));
+            }
+            self.debug_check_node(top);
+        }
+    }
+
+    // Checks the node's own height, balance and ordering against its
+    // children. Cheap enough to run after every change in debug builds;
+    // `check_invariants` checks the whole tree.
+    fn debug_check_node(&self, i: usize) {
+        if cfg!(debug_assertions) {
+            let node = &self.nodes[i];
+            debug_assert_eq!(node.height, 1 + self.height_of(node.left).max(self.height_of(node.right)));
+            debug_assert!(self.balance_factor(i).abs() <= 1, "node {} is unbalanced", i);
+            debug_assert!(node.left.is_none_or(|l| self.nodes[l].key < node.key));
+            debug_assert!(node.right.is_none_or(|r| self.nodes[r].key > node.key));
+        }
+    }
+
+    // The parent of a linked node, found by searching for its key.
+    fn parent_of(&self, i: usize) -> Option<usize> {
+        let key = &self.nodes[i].key;
+        let mut parent = None;
+        let mut current = self.root.expect("linked nodes have a root");
+
+        while current != i {
+            parent = Some(current);
+            let next = if *key < self.nodes[current].key { self.nodes[current].left } else { self.nodes[current].right };
+            current = next.expect("node is linked into the tree");
+        }
+
+        parent
+    }
+
+    // Removes an already unlinked node from the arena. The last node moves
+    // into its slot, so whatever linked to that one is repointed.
+    fn free(&mut self, i: usize) -> Node<K, V> {
+        let last = self.nodes.len() - 1;
+
+        if i != last {
+            let parent = self.parent_of(last);
+            self.replace_child(parent, last, Some(i));
+        }
+
+        self.nodes.swap_remove(i)
+    }
+
+    /// Inserts `value` under `key`, returning the value it replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let (path, found) = self.search(&key);
+
+        if found {
+            let node = &mut self.nodes[*path.last().expect("found keys have a path")];
+            if self.duplicates == Duplicates::Count {
+                node.count += 1;
+            }
+            return Some(mem::replace(&mut node.value, value));
+        }
+
+        let i = self.nodes.len();
+        match path.last() {
+            None => self.root = Some(i),
+            Some(&parent) if key < self.nodes[parent].key => self.nodes[parent].left = Some(i),
+            Some(&parent) => self.nodes[parent].right = Some(i),
+        }
+        self.nodes.push(Node::leaf(key, value));
+        self.rebalance_path(&path);
+
+        None
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        match self.find(&key) {
+            Some(index) => Entry::Occupied(OccupiedEntry { tree: self, index }),
+            None => Entry::Vacant(VacantEntry { tree: self, key }),
+        }
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).map(|i| &self.nodes[i].value)
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).map(|i| &mut self.nodes[i].value)
+    }
+
+    pub fn contains<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).is_some()
+    }
+
+    /// How many times `key` has been inserted, which is at most one unless
+    /// duplicates are counted.
+    pub fn count<Q: Ord + ?Sized>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        self.find(key).map_or(0, |i| self.nodes[i].count)
+    }
+
+    /// Removes `key` however many times it was counted, returning its value.
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let (mut path, found) = self.search(key);
+        if !found {
+            return None;
+        }
+
+        // A node with two children trades entries with its in-order
+        // successor, which has no left child, and that node is unlinked
+        // instead.
+        let target = *path.last().expect("found keys have a path");
+        let removed = match (self.nodes[target].left, self.nodes[target].right) {
+            (Some(_), Some(right)) => {
+                let mut successor = right;
+                path.push(successor);
+                while let Some(left) = self.nodes[successor].left {
+                    successor = left;
+                    path.push(successor);
+                }
+                self.swap_entries(target, successor);
+                successor
+            }
+            _ => target,
+        };
+
+        path.pop();
+        let child = self.nodes[removed].left.or(self.nodes[removed].right);
+        self.replace_child(path.last().copied(), removed, child);
+        self.rebalance_path(&path);
+
+        Some(self.free(removed).value)
+    }
+
+    /// Takes one off `key`'s count, removing it once the count reaches zero.
+    /// Returns `false` if the key was not there.
+    pub fn remove_one<Q: Ord + ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        match self.find(key) {
+            None => false,
+            Some(i) if self.nodes[i].count > 1 => {
+                self.nodes[i].count -= 1;
+                true
+            }
+            Some(_) => self.remove(key).is_some(),
+        }
+    }
+
+    pub fn min(&self) -> Option<&K> {
+        let mut i = self.root?;
+        while let Some(left) = self.nodes[i].left {
+            i = left;
+        }
+        Some(&self.nodes[i].key)
+    }
+
+    pub fn max(&self) -> Option<&K> {
+        let mut i = self.root?;
+        while let Some(right) = self.nodes[i].right {
+            i = right;
+        }
+        Some(&self.nodes[i].key)
+    }
+
+    /// Values within `range` in ascending key order. Subtrees entirely
+    /// outside the range are never visited.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut iter = Range { nodes: &self.nodes, stack: Vec::new(), last: None };
+
+        let mut link = self.root;
+        while let Some(i) = link {
+            let key = self.nodes[i].key.borrow();
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => key >= start,
+                Bound::Excluded(start) => key > start,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                iter.stack.push(i);
+                link = self.nodes[i].left;
+            } else {
+                link = self.nodes[i].right;
+            }
+        }
+
+        let mut link = self.root;
+        while let Some(i) = link {
+            let key = self.nodes[i].key.borrow();
+            let before_end = match range.end_bound() {
+                Bound::Included(end) => key <= end,
+                Bound::Excluded(end) => key < end,
+                Bound::Unbounded => true,
+            };
+            if before_end {
+                iter.last = Some(i);
+                link = self.nodes[i].right;
+            } else {
+                link = self.nodes[i].left;
+            }
+        }
+
+        // A range like 5..3, or one falling between two keys.
+        match (iter.stack.last(), iter.last) {
+            (Some(&first), Some(last)) if self.nodes[first].key <= self.nodes[last].key => {}
+            _ => iter.stack.clear(),
+        }
+
+        iter
+    }
+
+    /// Checks the whole tree: key order, stored heights, balance, and that
+    /// every node is reachable exactly once. Panics describing the first
+    /// problem found.
+    pub fn check_invariants(&self) {
+        let mut seen = vec![false; self.nodes.len()];
+        let mut stack: Vec<usize> = self.root.into_iter().collect();
+
+        while let Some(i) = stack.pop() {
+            assert!(!seen[i], "node {} is linked twice", i);
+            seen[i] = true;
+
+            let node = &self.nodes[i];
+            assert_eq!(node.height, 1 + self.height_of(node.left).max(self.height_of(node.right)), "height of node {}", i);
+            assert!(self.balance_factor(i).abs() <= 1, "node {} is unbalanced", i);
+            assert!(node.count >= 1, "node {} has a zero count", i);
+            stack.extend(node.left.into_iter().chain(node.right));
+        }
+
+        assert!(seen.iter().all(|&s| s), "some nodes are unreachable");
+        assert!(self.keys().zip(self.keys().skip(1)).all(|(a, b)| a < b), "keys are out of order");
+    }
+}
+
+impl<K: Ord> AvlTree<K> {
+    /// Adds `key` to a set, returning `false` if it was already there.
+    pub fn insert_value(&mut self, key: K) -> bool {
+        self.insert(key, ()).is_none()
+    }
+}
+
+impl<K: Ord, V> Default for AvlTree<K, V> {
+    fn default() -> AvlTree<K, V> {
+        AvlTree::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for AvlTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord> Extend<K> for AvlTree<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert_value(key);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for AvlTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> AvlTree<K, V> {
+        let mut tree = AvlTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord> FromIterator<K> for AvlTree<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> AvlTree<K> {
+        let mut tree = AvlTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a AvlTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<K, V> IntoIterator for AvlTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Entries in ascending key order.
+    fn into_iter(self) -> IntoIter<K, V> {
+        let order: Vec<usize> = {
+            let mut iter = Iter { nodes: &self.nodes, stack: Vec::new() };
+            iter.push_left_spine(self.root);
+            iter.indices().collect()
+        };
+        let mut slots: Vec<Option<Node<K, V>>> = self.nodes.into_iter().map(Some).collect();
+
+        let entries: Vec<(K, V)> = order
+            .into_iter()
+            .map(|i| slots[i].take().expect("each node is visited once"))
+            .map(|node| (node.key, node.value))
+            .collect();
+
+        IntoIter { entries: entries.into_iter() }
+    }
+}
+
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    tree: &'a mut AvlTree<K, V>,
+    index: usize,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    tree: &'a mut AvlTree<K, V>,
+    key: K,
+}
+
+impl<'a, K: Ord, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, modify: F) -> Entry<'a, K, V> {
+        if let Entry::Occupied(entry) = &mut self {
+            modify(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.tree.nodes[self.index].key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.tree.nodes[self.index].value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.tree.nodes[self.index].value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.tree.nodes[self.index].value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(self.get_mut(), value)
+    }
+
+    pub fn count(&self) -> usize {
+        self.tree.nodes[self.index].count
+    }
+}
+
+impl<'a, K: Ord, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        // New nodes go on the end of the arena and rotations only relink
+        // them, so the index is known up front.
+        let index = self.tree.nodes.len();
+        self.tree.insert(self.key, value);
+        &mut self.tree.nodes[index].value
+    }
+}
+
+/// In-order iterator over a whole [`AvlTree`].
+pub struct Iter<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    // Nodes whose entry and right subtree are still to come, deepest last.
+    stack: Vec<usize>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut link: Option<usize>) {
+        while let Some(i) = link {
+            self.stack.push(i);
+            link = self.nodes[i].left;
+        }
+    }
+
+    fn next_index(&mut self) -> Option<usize> {
+        let i = self.stack.pop()?;
+        self.push_left_spine(self.nodes[i].right);
+        Some(i)
+    }
+
+    fn indices(mut self) -> impl Iterator<Item = usize> + 'a {
+        std::iter::from_fn(move || self.next_index())
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = &self.nodes[self.next_index()?];
+        Some((&node.key, &node.value))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.stack.len(), Some(self.nodes.len()))
+    }
+}
+
+/// In-order iterator over the entries within a key range.
+pub struct Range<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    stack: Vec<usize>,
+    // The greatest node in range; iteration stops after it.
+    last: Option<usize>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let i = self.stack.pop()?;
+
+        if self.last == Some(i) {
+            self.stack.clear();
+        } else {
+            let mut link = self.nodes[i].right;
+            while let Some(child) = link {
+                self.stack.push(child);
+                link = self.nodes[child].left;
+            }
+        }
+
+        let node = &self.nodes[i];
+        Some((&node.key, &node.value))
+    }
+}
+
+pub struct IntoIter<K, V> {
+    entries: std::vec::IntoIter<(K, V)>,
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        self.entries.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.entries.size_hint()
+    }
+}
+
+pub struct PreOrder<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    stack: Vec<usize>,
+}
+
+impl<'a, K, V> Iterator for PreOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = &self.nodes[self.stack.pop()?];
+        self.stack.extend(node.right);
+        self.stack.extend(node.left);
+        Some((&node.key, &node.value))
+    }
+}
+
+pub struct PostOrder<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    // Each node is pushed once to visit its children and again, marked
+    // `true`, to be yielded after them.
+    stack: Vec<(usize, bool)>,
+}
+
+impl<'a, K, V> Iterator for PostOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            let (i, children_done) = self.stack.pop()?;
+            let node = &self.nodes[i];
+            if children_done {
+                return Some((&node.key, &node.value));
+            }
+
+            self.stack.push((i, true));
+            self.stack.extend(node.right.map(|n| (n, false)));
+            self.stack.extend(node.left.map(|n| (n, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<'a, K, V> {
+    nodes: &'a [Node<K, V>],
+    queue: VecDeque<usize>,
+}
+
+impl<'a, K, V> Iterator for LevelOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = &self.nodes[self.queue.pop_front()?];
+        self.queue.extend(node.left);
+        self.queue.extend(node.right);
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use super::*;
+    use crate::test_util::Rng;
+
+    fn keys<'a, K: Copy + 'a, V: 'a>(entries: impl Iterator<Item = (&'a K, &'a V)>) -> Vec<K> {
+        entries.map(|(key, _)| *key).collect()
+    }
+
+    // The largest height an AVL tree of `len` nodes can have.
+    fn max_height(len: usize) -> usize {
+        (1.4405 * ((len + 2) as f64).log2() - 0.3277).floor() as usize
+    }
+
+    #[test]
+    fn should_stay_balanced_on_sorted_input() {
+        let ascending: AvlTree<u32> = (0..100_000).collect();
+        let descending: AvlTree<u32> = (0..100_000).rev().collect();
+
+        ascending.check_invariants();
+        descending.check_invariants();
+        assert_eq!(ascending.height(), 17);
+        assert!(descending.height() <= max_height(100_000));
+        assert_eq!(ascending.keys().copied().collect::<Vec<_>>(), descending.keys().copied().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn should_rotate_small_trees() {
+        // Left-right case: 30, 10, 20 must end up with 20 on top.
+        let tree: AvlTree<u32> = [30, 10, 20].into_iter().collect();
+
+        assert_eq!(keys(tree.pre_order()), vec![20, 10, 30]);
+        assert_eq!(keys(tree.post_order()), vec![10, 30, 20]);
+        assert_eq!(keys(tree.level_order()), vec![20, 10, 30]);
+        assert_eq!(tree.height(), 2);
+    }
+
+    #[test]
+    fn should_rebalance_after_removal() {
+        let mut tree: AvlTree<u32> = (1..=7).collect();
+
+        for key in [1, 2, 3] {
+            assert!(tree.remove(&key).is_some());
+            tree.check_invariants();
+        }
+
+        assert_eq!(keys(tree.level_order()), vec![6, 4, 7, 5]);
+        assert_eq!((tree.min(), tree.max()), (Some(&4), Some(&7)));
+        assert!(tree.remove(&3).is_none());
+    }
+
+    #[test]
+    fn should_share_binary_tree_api() {
+        let mut words: AvlTree<&str, usize> = AvlTree::new();
+        for word in "the cat saw the other cat and the dog".split(' ') {
+            *words.entry(word).or_default() += 1;
+        }
+        words.entry("cat").and_modify(|n| *n *= 10).or_insert(0);
+
+        assert_eq!(words.get("the"), Some(&3));
+        assert_eq!(words.get("cat"), Some(&20));
+        assert_eq!(keys(words.range("cat".."saw")), vec!["cat", "dog", "other"]);
+        assert_eq!(words.into_iter().map(|(_, n)| n).sum::<usize>(), 3 + 20 + 4);
+
+        let mut bag: AvlTree<char> = AvlTree::with_duplicates(Duplicates::Count);
+        bag.extend("mississippi".chars());
+        assert_eq!((bag.len(), bag.count(&'s')), (4, 4));
+        assert!(bag.remove_one(&'m'));
+        assert_eq!(bag.keys().collect::<String>(), "ips");
+    }
+
+    #[test]
+    fn should_match_btreemap_under_random_operations() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..20 {
+            let mut tree = AvlTree::new();
+            let mut expected = BTreeMap::new();
+
+            for step in 0..2000 {
+                let key = rng.below(300);
+                match rng.below(3) {
+                    0 | 1 => assert_eq!(tree.insert(key, step), expected.insert(key, step)),
+                    _ => assert_eq!(tree.remove(&key), expected.remove(&key)),
+                }
+                assert_eq!(tree.get(&key), expected.get(&key));
+            }
+            tree.check_invariants();
+
+            let (low, high) = (rng.below(300), rng.below(300));
+            let (low, high) = (low.min(high), low.max(high));
+
+            assert_eq!(tree.len(), expected.len());
+            assert!(tree.height() <= max_height(tree.len()));
+            assert_eq!(tree.iter().collect::<Vec<_>>(), expected.iter().collect::<Vec<_>>());
+            assert_eq!(tree.range(low..=high).collect::<Vec<_>>(), expected.range(low..=high).collect::<Vec<_>>());
+            assert_eq!(tree.min(), expected.keys().next());
+            assert_eq!(tree.max(), expected.keys().next_back());
+        }
+    }
+}
@@ -1,38 +1,889 @@
+use std::borrow::Borrow;
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::fmt;
+use std::mem;
+use std::ops::{Bound, RangeBounds};
 
-#[derive(Debug)]
-pub struct BinaryTree {
-    value: u32,
-    left: Option<Box<BinaryTree>>,
-    right: Option<Box<BinaryTree>>
+mod render;
+
+type Link<K, V> = Option<Box<Node<K, V>>>;
+
+struct Node<K, V> {
+    key: K,
+    value: V,
+    count: usize,
+    left: Link<K, V>,
+    right: Link<K, V>,
+}
+
+impl<K, V> Node<K, V> {
+    fn leaf(key: K, value: V) -> Box<Node<K, V>> {
+        Box::new(Node { key, value, count: 1, left: None, right: None })
+    }
+}
+
+/// What inserting a key that is already present does.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Duplicates {
+    /// Keep one copy, replacing its value.
+    #[default]
+    Replace,
+    /// Replace the value and also count the key again, making the tree a
+    /// multiset. See [`BinaryTree::count`] and [`BinaryTree::remove_one`].
+    Count,
+}
+
+/// A binary search tree mapping ordered keys to values. With the default
+/// `V = ()` it is a set.
+pub struct BinaryTree<K, V = ()> {
+    root: Link<K, V>,
+    len: usize,
+    duplicates: Duplicates,
+}
+
+// The link holding `key`, or the empty link where it would be inserted.
+fn find_link<'a, K, V, Q>(mut link: &'a mut Link<K, V>, key: &Q) -> &'a mut Link<K, V>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    loop {
+        let ordering = match link {
+            Some(node) => key.cmp(node.key.borrow()),
+            None => return link,
+        };
+        match ordering {
+            Ordering::Less => link = &mut link.as_mut().expect("checked above").left,
+            Ordering::Greater => link = &mut link.as_mut().expect("checked above").right,
+            Ordering::Equal => return link,
+        }
+    }
+}
+
+fn find<'a, K, V, Q>(mut link: &'a Link<K, V>, key: &Q) -> Option<&'a Node<K, V>>
+where
+    K: Borrow<Q>,
+    Q: Ord + ?Sized,
+{
+    while let Some(node) = link {
+        match key.cmp(node.key.borrow()) {
+            Ordering::Less => link = &node.left,
+            Ordering::Greater => link = &node.right,
+            Ordering::Equal => return Some(node),
+        }
+    }
+    None
+}
+
+// Takes the node out of a non-empty `link`, splicing its children back in.
+// A node with two children is replaced by its in-order successor.
+fn unlink<K, V>(link: &mut Link<K, V>) -> Box<Node<K, V>> {
+    let mut node = link.take().expect("unlink needs a node");
+
+    *link = match (node.left.take(), node.right.take()) {
+        (None, None) => None,
+        (Some(child), None) | (None, Some(child)) => Some(child),
+        (Some(left), Some(right)) => {
+            let mut right = Some(right);
+            let mut successor = take_min(&mut right);
+            successor.left = Some(left);
+            successor.right = right;
+            Some(successor)
+        }
+    };
+
+    node
 }
 
-impl BinaryTree {
-    pub fn new(value: u32) -> BinaryTree {
-        BinaryTree { value, left: None, right: None, }
+// Unlinks the smallest node under a non-empty `link`.
+fn take_min<K, V>(mut link: &mut Link<K, V>) -> Box<Node<K, V>> {
+    while link.as_ref().is_some_and(|node| node.left.is_some()) {
+        link = &mut link.as_mut().expect("checked above").left;
+    }
+
+    let mut node = link.take().expect("take_min needs a node");
+    *link = node.right.take();
+    node
+}
+
+impl<K: Ord, V> BinaryTree<K, V> {
+    pub fn new() -> BinaryTree<K, V> {
+        BinaryTree::with_duplicates(Duplicates::Replace)
+    }
+
+    pub fn with_duplicates(duplicates: Duplicates) -> BinaryTree<K, V> {
+        BinaryTree { root: None, len: 0, duplicates }
     }
 
-    pub fn insert_value(&mut self, value: u32) {
-        if value > self.value {
-            match self.right.as_mut() {
-                Some(v) => {
-                    v.insert_value(value);
-                },
-                None =>  {
-                    let leaf = BinaryTree {value, left: None, right: None,};
-                    self.right = Some(Box::new(leaf));
+    /// Inserts `value` under `key`, returning the value it replaced.
+    pub fn insert(&mut self, key: K, value: V) -> Option<V> {
+        let link = find_link(&mut self.root, &key);
+
+        match link {
+            Some(node) => {
+                if self.duplicates == Duplicates::Count {
+                    node.count += 1;
                 }
+                Some(mem::replace(&mut node.value, value))
+            }
+            None => {
+                *link = Some(Node::leaf(key, value));
+                self.len += 1;
+                None
+            }
+        }
+    }
+
+    pub fn entry(&mut self, key: K) -> Entry<'_, K, V> {
+        let link = find_link(&mut self.root, &key);
+
+        if link.is_some() {
+            Entry::Occupied(OccupiedEntry { node: link.as_mut().expect("checked above") })
+        } else {
+            Entry::Vacant(VacantEntry { key, link, len: &mut self.len })
+        }
+    }
+
+    pub fn get<Q: Ord + ?Sized>(&self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+    {
+        find(&self.root, key).map(|node| &node.value)
+    }
+
+    pub fn get_mut<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<&mut V>
+    where
+        K: Borrow<Q>,
+    {
+        find_link(&mut self.root, key).as_mut().map(|node| &mut node.value)
+    }
+
+    pub fn contains<Q: Ord + ?Sized>(&self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        find(&self.root, key).is_some()
+    }
+
+    /// How many times `key` has been inserted, which is at most one unless
+    /// duplicates are counted.
+    pub fn count<Q: Ord + ?Sized>(&self, key: &Q) -> usize
+    where
+        K: Borrow<Q>,
+    {
+        find(&self.root, key).map_or(0, |node| node.count)
+    }
+
+    /// Removes `key` however many times it was counted, returning its value.
+    pub fn remove<Q: Ord + ?Sized>(&mut self, key: &Q) -> Option<V>
+    where
+        K: Borrow<Q>,
+    {
+        let link = find_link(&mut self.root, key);
+        link.as_ref()?;
+
+        self.len -= 1;
+        Some(unlink(link).value)
+    }
+
+    /// Takes one off `key`'s count, removing it once the count reaches zero.
+    /// Returns `false` if the key was not there.
+    pub fn remove_one<Q: Ord + ?Sized>(&mut self, key: &Q) -> bool
+    where
+        K: Borrow<Q>,
+    {
+        let link = find_link(&mut self.root, key);
+        let Some(node) = link else {
+            return false;
+        };
+
+        if node.count > 1 {
+            node.count -= 1;
+        } else {
+            unlink(link);
+            self.len -= 1;
+        }
+        true
+    }
+
+    pub fn min(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(left) = &node.left {
+            node = left;
+        }
+        Some(&node.key)
+    }
+
+    pub fn max(&self) -> Option<&K> {
+        let mut node = self.root.as_ref()?;
+        while let Some(right) = &node.right {
+            node = right;
+        }
+        Some(&node.key)
+    }
+
+    /// Values within `range` in ascending key order. Subtrees entirely
+    /// outside the range are never visited.
+    pub fn range<Q, R>(&self, range: R) -> Range<'_, K, V>
+    where
+        K: Borrow<Q>,
+        Q: Ord + ?Sized,
+        R: RangeBounds<Q>,
+    {
+        let mut iter = Range { stack: Vec::new(), last: None };
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let after_start = match range.start_bound() {
+                Bound::Included(start) => node.key.borrow() >= start,
+                Bound::Excluded(start) => node.key.borrow() > start,
+                Bound::Unbounded => true,
+            };
+            if after_start {
+                iter.stack.push(node);
+                link = &node.left;
+            } else {
+                link = &node.right;
+            }
+        }
+
+        let mut link = &self.root;
+        while let Some(node) = link {
+            let before_end = match range.end_bound() {
+                Bound::Included(end) => node.key.borrow() <= end,
+                Bound::Excluded(end) => node.key.borrow() < end,
+                Bound::Unbounded => true,
+            };
+            if before_end {
+                iter.last = Some(node);
+                link = &node.right;
+            } else {
+                link = &node.left;
+            }
+        }
+
+        // A range like 5..3, or one falling between two keys.
+        match (iter.stack.last(), iter.last) {
+            (Some(first), Some(last)) if first.key <= last.key => {}
+            _ => iter.stack.clear(),
+        }
+
+        iter
+    }
+}
+
+impl<K, V> BinaryTree<K, V> {
+    /// Number of distinct keys.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn duplicates(&self) -> Duplicates {
+        self.duplicates
+    }
+
+    /// Number of nodes on the longest path from the root to a leaf; zero
+    /// for an empty tree.
+    ///
+    /// Counted level by level rather than recursively, since an unbalanced
+    /// tree can be as deep as it is long.
+    pub fn height(&self) -> usize {
+        let mut level: Vec<&Node<K, V>> = self.root.as_deref().into_iter().collect();
+        let mut height = 0;
+
+        while !level.is_empty() {
+            height += 1;
+            level = level.iter().flat_map(|node| node.left.as_deref().into_iter().chain(node.right.as_deref())).collect();
+        }
+
+        height
+    }
+
+    /// Entries in ascending key order.
+    pub fn iter(&self) -> Iter<'_, K, V> {
+        let mut iter = Iter { stack: Vec::new() };
+        iter.push_left_spine(&self.root);
+        iter
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.iter().map(|(key, _)| key)
+    }
+
+    pub fn values(&self) -> impl Iterator<Item = &V> {
+        self.iter().map(|(_, value)| value)
+    }
+
+    pub fn pre_order(&self) -> PreOrder<'_, K, V> {
+        PreOrder { stack: self.root.as_deref().into_iter().collect() }
+    }
+
+    pub fn post_order(&self) -> PostOrder<'_, K, V> {
+        PostOrder { stack: self.root.as_deref().map(|node| (node, false)).into_iter().collect() }
+    }
+
+    /// Entries a level at a time from the root down, left to right.
+    pub fn level_order(&self) -> LevelOrder<'_, K, V> {
+        LevelOrder { queue: self.root.as_deref().into_iter().collect() }
+    }
+}
+
+impl<K: Ord> BinaryTree<K> {
+    /// Adds `key` to a set, returning `false` if it was already there.
+    pub fn insert_value(&mut self, key: K) -> bool {
+        self.insert(key, ()).is_none()
+    }
+}
+
+impl<K: Ord, V> Default for BinaryTree<K, V> {
+    fn default() -> BinaryTree<K, V> {
+        BinaryTree::new()
+    }
+}
+
+impl<K: Ord, V> Extend<(K, V)> for BinaryTree<K, V> {
+    fn extend<I: IntoIterator<Item = (K, V)>>(&mut self, iter: I) {
+        for (key, value) in iter {
+            self.insert(key, value);
+        }
+    }
+}
+
+impl<K: Ord> Extend<K> for BinaryTree<K> {
+    fn extend<I: IntoIterator<Item = K>>(&mut self, iter: I) {
+        for key in iter {
+            self.insert_value(key);
+        }
+    }
+}
+
+impl<K: Ord, V> FromIterator<(K, V)> for BinaryTree<K, V> {
+    fn from_iter<I: IntoIterator<Item = (K, V)>>(iter: I) -> BinaryTree<K, V> {
+        let mut tree = BinaryTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<K: Ord> FromIterator<K> for BinaryTree<K> {
+    fn from_iter<I: IntoIterator<Item = K>>(iter: I) -> BinaryTree<K> {
+        let mut tree = BinaryTree::new();
+        tree.extend(iter);
+        tree
+    }
+}
+
+impl<'a, K, V> IntoIterator for &'a BinaryTree<K, V> {
+    type Item = (&'a K, &'a V);
+    type IntoIter = Iter<'a, K, V>;
+
+    fn into_iter(self) -> Iter<'a, K, V> {
+        self.iter()
+    }
+}
+
+impl<K, V> IntoIterator for BinaryTree<K, V> {
+    type Item = (K, V);
+    type IntoIter = IntoIter<K, V>;
+
+    /// Entries in ascending key order, freeing the nodes as it goes.
+    fn into_iter(mut self) -> IntoIter<K, V> {
+        let mut iter = IntoIter { stack: Vec::new() };
+        iter.push_left_spine(self.root.take());
+        iter
+    }
+}
+
+// The default drop would recurse once per level and overflow the stack on a
+// tree built from sorted keys, so nodes are unlinked one at a time instead.
+impl<K, V> Drop for BinaryTree<K, V> {
+    fn drop(&mut self) {
+        let mut stack: Vec<Box<Node<K, V>>> = self.root.take().into_iter().collect();
+
+        while let Some(mut node) = stack.pop() {
+            stack.extend(node.left.take());
+            stack.extend(node.right.take());
+        }
+    }
+}
+
+// Like `Drop`, and for the same reason: the copy is built bottom-up from a
+// post-order walk, with finished subtrees waiting on a stack for their parent.
+impl<K: Clone, V: Clone> Clone for BinaryTree<K, V> {
+    fn clone(&self) -> BinaryTree<K, V> {
+        let mut stack: Vec<(&Node<K, V>, bool)> = self.root.as_deref().map(|node| (node, false)).into_iter().collect();
+        let mut done: Vec<Box<Node<K, V>>> = Vec::new();
+
+        while let Some((node, children_done)) = stack.pop() {
+            if !children_done {
+                stack.push((node, true));
+                stack.extend(node.right.as_deref().map(|n| (n, false)));
+                stack.extend(node.left.as_deref().map(|n| (n, false)));
+                continue;
             }
-            
-        }else if value < self.value {
-            match self.left.as_mut() {
-                Some(v) => {
-                    v.insert_value(value);
-                },
-                None =>  {
-                    let leaf = BinaryTree {value, left: None, right: None,};
-                    self.left = Some(Box::new(leaf));
+
+            // The right subtree finished last, so it is on top.
+            let right = node.right.as_ref().and_then(|_| done.pop());
+            let left = node.left.as_ref().and_then(|_| done.pop());
+            done.push(Box::new(Node { key: node.key.clone(), value: node.value.clone(), count: node.count, left, right }));
+        }
+
+        BinaryTree { root: done.pop(), len: self.len, duplicates: self.duplicates }
+    }
+}
+
+impl<K: fmt::Debug, V: fmt::Debug> fmt::Debug for BinaryTree<K, V> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_map().entries(self.iter()).finish()
+    }
+}
+
+pub enum Entry<'a, K, V> {
+    Occupied(OccupiedEntry<'a, K, V>),
+    Vacant(VacantEntry<'a, K, V>),
+}
+
+pub struct OccupiedEntry<'a, K, V> {
+    node: &'a mut Node<K, V>,
+}
+
+pub struct VacantEntry<'a, K, V> {
+    key: K,
+    link: &'a mut Link<K, V>,
+    len: &'a mut usize,
+}
+
+impl<'a, K, V> Entry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        match self {
+            Entry::Occupied(entry) => entry.key(),
+            Entry::Vacant(entry) => entry.key(),
+        }
+    }
+
+    pub fn or_insert(self, default: V) -> &'a mut V {
+        self.or_insert_with(|| default)
+    }
+
+    pub fn or_insert_with<F: FnOnce() -> V>(self, default: F) -> &'a mut V {
+        match self {
+            Entry::Occupied(entry) => entry.into_mut(),
+            Entry::Vacant(entry) => entry.insert(default()),
+        }
+    }
+
+    pub fn or_default(self) -> &'a mut V
+    where
+        V: Default,
+    {
+        self.or_insert_with(V::default)
+    }
+
+    pub fn and_modify<F: FnOnce(&mut V)>(mut self, modify: F) -> Entry<'a, K, V> {
+        if let Entry::Occupied(entry) = &mut self {
+            modify(entry.get_mut());
+        }
+        self
+    }
+}
+
+impl<'a, K, V> OccupiedEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.node.key
+    }
+
+    pub fn get(&self) -> &V {
+        &self.node.value
+    }
+
+    pub fn get_mut(&mut self) -> &mut V {
+        &mut self.node.value
+    }
+
+    pub fn into_mut(self) -> &'a mut V {
+        &mut self.node.value
+    }
+
+    pub fn insert(&mut self, value: V) -> V {
+        mem::replace(&mut self.node.value, value)
+    }
+
+    pub fn count(&self) -> usize {
+        self.node.count
+    }
+}
+
+impl<'a, K, V> VacantEntry<'a, K, V> {
+    pub fn key(&self) -> &K {
+        &self.key
+    }
+
+    pub fn insert(self, value: V) -> &'a mut V {
+        *self.len += 1;
+        &mut self.link.insert(Node::leaf(self.key, value)).value
+    }
+}
+
+/// In-order iterator over a whole [`BinaryTree`].
+pub struct Iter<'a, K, V> {
+    // Nodes whose entry and right subtree are still to come, deepest last.
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iter<'a, K, V> {
+    fn push_left_spine(&mut self, mut link: &'a Link<K, V>) {
+        while let Some(node) = link {
+            self.stack.push(node);
+            link = &node.left;
+        }
+    }
+}
+
+impl<'a, K, V> Iterator for Iter<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.stack.pop()?;
+        self.push_left_spine(&node.right);
+        Some((&node.key, &node.value))
+    }
+}
+
+/// In-order iterator over the entries within a key range.
+pub struct Range<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+    // The greatest node in range; iteration stops after it.
+    last: Option<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for Range<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.stack.pop()?;
+
+        if self.last.is_some_and(|last| std::ptr::eq(last, node)) {
+            self.stack.clear();
+        } else {
+            let mut link = &node.right;
+            while let Some(child) = link {
+                self.stack.push(child);
+                link = &child.left;
+            }
+        }
+
+        Some((&node.key, &node.value))
+    }
+}
+
+pub struct IntoIter<K, V> {
+    stack: Vec<Box<Node<K, V>>>,
+}
+
+impl<K, V> IntoIter<K, V> {
+    fn push_left_spine(&mut self, mut link: Link<K, V>) {
+        while let Some(mut node) = link {
+            link = node.left.take();
+            self.stack.push(node);
+        }
+    }
+}
+
+impl<K, V> Iterator for IntoIter<K, V> {
+    type Item = (K, V);
+
+    fn next(&mut self) -> Option<(K, V)> {
+        let mut node = self.stack.pop()?;
+        self.push_left_spine(node.right.take());
+        Some((node.key, node.value))
+    }
+}
+
+// The nodes on the stack still own their right subtrees, which would be
+// dropped recursively.
+impl<K, V> Drop for IntoIter<K, V> {
+    fn drop(&mut self) {
+        for _ in self.by_ref() {}
+    }
+}
+
+pub struct PreOrder<'a, K, V> {
+    stack: Vec<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for PreOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.right.as_deref());
+        self.stack.extend(node.left.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+pub struct PostOrder<'a, K, V> {
+    // Each node is pushed once to visit its children and again, marked
+    // `true`, to be yielded after them.
+    stack: Vec<(&'a Node<K, V>, bool)>,
+}
+
+impl<'a, K, V> Iterator for PostOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        loop {
+            let (node, children_done) = self.stack.pop()?;
+            if children_done {
+                return Some((&node.key, &node.value));
+            }
+
+            self.stack.push((node, true));
+            self.stack.extend(node.right.as_deref().map(|n| (n, false)));
+            self.stack.extend(node.left.as_deref().map(|n| (n, false)));
+        }
+    }
+}
+
+pub struct LevelOrder<'a, K, V> {
+    queue: VecDeque<&'a Node<K, V>>,
+}
+
+impl<'a, K, V> Iterator for LevelOrder<'a, K, V> {
+    type Item = (&'a K, &'a V);
+
+    fn next(&mut self) -> Option<(&'a K, &'a V)> {
+        let node = self.queue.pop_front()?;
+        self.queue.extend(node.left.as_deref());
+        self.queue.extend(node.right.as_deref());
+        Some((&node.key, &node.value))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::{BTreeMap, BTreeSet};
+
+    use super::*;
+    use crate::test_util::{sample, Rng};
+
+    fn keys<'a, K: Copy + 'a, V: 'a>(entries: impl Iterator<Item = (&'a K, &'a V)>) -> Vec<K> {
+        entries.map(|(key, _)| *key).collect()
+    }
+
+    #[test]
+    fn should_reject_duplicates() {
+        let mut tree = sample();
+
+        assert!(!tree.insert_value(6));
+        assert!(tree.insert_value(7));
+        assert_eq!(tree.len(), 8);
+        assert_eq!(tree.count(&6), 1);
+    }
+
+    #[test]
+    fn should_traverse_in_every_order() {
+        let tree = sample();
+
+        assert_eq!(keys(tree.iter()), vec![3, 5, 6, 10, 18, 25, 60]);
+        assert_eq!(keys(tree.pre_order()), vec![10, 5, 3, 6, 60, 25, 18]);
+        assert_eq!(keys(tree.post_order()), vec![3, 6, 5, 18, 25, 60, 10]);
+        assert_eq!(keys(tree.level_order()), vec![10, 5, 60, 3, 6, 25, 18]);
+        assert_eq!((tree.min(), tree.max(), tree.height()), (Some(&3), Some(&60), 4));
+    }
+
+    #[test]
+    fn should_remove_nodes_with_any_number_of_children() {
+        let mut tree = sample();
+
+        assert!(tree.remove(&3).is_some()); // leaf
+        assert!(tree.remove(&25).is_some()); // one child
+        assert!(tree.remove(&10).is_some()); // two children, at the root
+        assert!(tree.remove(&10).is_none());
+
+        assert_eq!(keys(tree.pre_order()), vec![18, 5, 6, 60]);
+        assert_eq!(tree.len(), 4);
+    }
+
+    #[test]
+    fn should_start_and_end_empty() {
+        let mut tree = BinaryTree::new();
+
+        assert!(tree.is_empty());
+        assert_eq!((tree.min(), tree.max(), tree.height()), (None, None, 0));
+        assert!(tree.insert_value(1));
+        assert!(tree.remove(&1).is_some());
+        assert!(tree.is_empty());
+        assert_eq!(tree.iter().next(), None);
+    }
+
+    #[test]
+    fn should_query_ranges() {
+        let tree = sample();
+
+        assert_eq!(keys(tree.range(5..25)), vec![5, 6, 10, 18]);
+        assert_eq!(keys(tree.range(4..=25)), vec![5, 6, 10, 18, 25]);
+        assert_eq!(keys(tree.range((Bound::Excluded(10), Bound::Unbounded))), vec![18, 25, 60]);
+        assert_eq!(keys(tree.range(..6)), vec![3, 5]);
+        assert_eq!(keys(tree.range(7..10)), Vec::<u32>::new());
+        assert_eq!(tree.range(61..).next(), None);
+    }
+
+    #[test]
+    fn should_map_keys_to_values() {
+        let mut ages: BinaryTree<String, u32> =
+            [("ada".to_owned(), 36), ("alan".to_owned(), 41)].into_iter().collect();
+
+        assert_eq!(ages.insert("ada".to_owned(), 37), Some(36));
+        assert_eq!(ages.get("ada"), Some(&37));
+        *ages.get_mut("alan").unwrap() += 1;
+        assert_eq!(ages.values().copied().collect::<Vec<_>>(), vec![37, 42]);
+        assert_eq!(ages.remove("grace"), None);
+        assert_eq!(ages.into_iter().collect::<Vec<_>>(), vec![("ada".to_owned(), 37), ("alan".to_owned(), 42)]);
+    }
+
+    #[test]
+    fn should_update_through_entries() {
+        let mut words: BinaryTree<&str, usize> = BinaryTree::new();
+
+        for word in "the cat saw the other cat and the dog".split(' ') {
+            *words.entry(word).or_default() += 1;
+        }
+        words.entry("cat").and_modify(|n| *n *= 10).or_insert(0);
+        words.entry("emu").and_modify(|n| *n *= 10).or_insert(7);
+
+        assert_eq!(words.get("the"), Some(&3));
+        assert_eq!(words.get("cat"), Some(&20));
+        assert_eq!(words.get("emu"), Some(&7));
+        assert_eq!(words.len(), 7);
+        match words.entry("dog") {
+            Entry::Occupied(mut entry) => assert_eq!(entry.insert(5), 1),
+            Entry::Vacant(_) => panic!("dog was counted"),
+        }
+        assert_eq!(words.entry("fox").key(), &"fox");
+    }
+
+    #[test]
+    fn should_count_duplicates_as_a_multiset() {
+        let mut bag: BinaryTree<char> = BinaryTree::with_duplicates(Duplicates::Count);
+        bag.extend("mississippi".chars());
+
+        assert_eq!(bag.len(), 4);
+        assert_eq!(bag.count(&'s'), 4);
+        assert!(bag.remove_one(&'m'));
+        assert!(bag.remove_one(&'p'));
+
+        assert!(!bag.contains(&'m'));
+        assert_eq!(bag.count(&'p'), 1);
+        assert!(bag.remove(&'s').is_some());
+        assert_eq!(bag.keys().copied().collect::<String>(), "ip");
+        assert!(!bag.remove_one(&'z'));
+    }
+
+    #[test]
+    fn should_match_btreeset_under_random_operations() {
+        let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+
+        for _ in 0..50 {
+            let mut tree = BinaryTree::new();
+            let mut expected = BTreeSet::new();
+
+            for _ in 0..200 {
+                let value = rng.below(100);
+                match rng.below(3) {
+                    0 | 1 => assert_eq!(tree.insert_value(value), expected.insert(value)),
+                    _ => assert_eq!(tree.remove(&value).is_some(), expected.remove(&value)),
                 }
+                assert_eq!(tree.contains(&value), expected.contains(&value));
             }
+
+            let (low, high) = (rng.below(100), rng.below(100));
+            let (low, high) = (low.min(high), low.max(high));
+
+            assert_eq!(tree.len(), expected.len());
+            assert_eq!(tree.keys().collect::<Vec<_>>(), expected.iter().collect::<Vec<_>>());
+            assert_eq!(keys(tree.range(low..high)), expected.range(low..high).copied().collect::<Vec<_>>());
+            assert_eq!(tree.min(), expected.first());
+            assert_eq!(tree.max(), expected.last());
+
+            let mut pre = keys(tree.pre_order());
+            let mut post = keys(tree.post_order());
+            let mut level = keys(tree.level_order());
+            pre.sort();
+            post.sort();
+            level.sort();
+            assert_eq!(pre, keys(tree.iter()));
+            assert_eq!(post, pre);
+            assert_eq!(level, pre);
+            assert!(tree.height() <= tree.len());
         }
-    }  
-}
\ No newline at end of file
+    }
+
+    #[test]
+    fn should_match_btreemap_with_counted_duplicates() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        let mut tree = BinaryTree::with_duplicates(Duplicates::Count);
+        let mut expected: BTreeMap<u32, usize> = BTreeMap::new();
+
+        for _ in 0..2000 {
+            let key = rng.below(50);
+            if rng.below(2) == 0 {
+                tree.insert(key, ());
+                *expected.entry(key).or_default() += 1;
+            } else {
+                let present = expected.contains_key(&key);
+                assert_eq!(tree.remove_one(&key), present);
+                if present && expected[&key] == 1 {
+                    expected.remove(&key);
+                } else if present {
+                    *expected.get_mut(&key).unwrap() -= 1;
+                }
+            }
+        }
+
+        let counts: Vec<(u32, usize)> = tree.keys().map(|&key| (key, tree.count(&key))).collect();
+        assert_eq!(counts, expected.into_iter().collect::<Vec<_>>());
+    }
+
+    // What inserting `0..n` in order produces, linked up directly since the
+    // inserts themselves would take quadratic time.
+    fn sorted_chain(n: u32) -> BinaryTree<u32> {
+        let mut tree = BinaryTree::new();
+        for key in (0..n).rev() {
+            let mut node = Node::leaf(key, ());
+            node.right = tree.root.take();
+            tree.root = Some(node);
+        }
+        tree.len = n as usize;
+        tree
+    }
+
+    #[test]
+    fn should_clone_and_format_without_recursion() {
+        let tree = sorted_chain(200_000);
+
+        let copy = tree.clone();
+        assert_eq!(copy.len(), 200_000);
+        assert!(copy.keys().copied().eq(0..200_000));
+        assert!(format!("{:?}", tree).ends_with("199999: ()}"));
+
+        let mut sample = sample();
+        let copy = sample.clone();
+        sample.remove(&10);
+        assert_eq!(keys(copy.pre_order()), [10, 5, 3, 6, 60, 25, 18]);
+        assert_eq!(format!("{:?}", [(2, 'b'), (1, 'a')].into_iter().collect::<BinaryTree<i32, char>>()), "{1: 'a', 2: 'b'}");
+    }
+
+    #[test]
+    fn should_drop_a_partly_consumed_deep_into_iter() {
+        let mut iter = sorted_chain(200_000).into_iter();
+
+        assert_eq!(iter.next(), Some((0, ())));
+        drop(iter);
+    }
+}
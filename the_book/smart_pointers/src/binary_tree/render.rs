@@ -0,0 +1,168 @@
+//! Text pictures of a [`BinaryTree`]: a sideways drawing for the terminal
+//! and Graphviz DOT for anything bigger.
+//!
+//! Both walk the tree with an explicit stack, so they cope with the deep,
+//! list-like trees sorted input produces.
+
+use std::fmt::{Display, Write};
+
+use super::{BinaryTree, Node};
+
+// Lines still to be written, in reverse.
+enum Step<'a, K, V> {
+    Node { node: &'a Node<K, V>, prefix: String, side: Side },
+    Line(String),
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Side {
+    Root,
+    Right,
+    Left,
+}
+
+fn escape(label: &str) -> String {
+    label.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+impl<K, V> BinaryTree<K, V> {
+    /// Draws the tree on its side with the root on the left, larger keys
+    /// above and smaller keys below:
+    ///
+    /// ```text
+    /// ┌── 60
+    /// │   └── 25
+    /// 10
+    /// │   ┌── 6
+    /// └── 5
+    ///     └── 3
+    /// ```
+    pub fn to_ascii(&self) -> String
+    where
+        K: Display,
+    {
+        self.to_ascii_with(|key, _| key.to_string())
+    }
+
+    /// Like [`to_ascii`](BinaryTree::to_ascii), labelling each node with
+    /// `label(key, value)`.
+    pub fn to_ascii_with<F: Fn(&K, &V) -> String>(&self, label: F) -> String {
+        let mut out = String::new();
+        let mut steps: Vec<Step<K, V>> =
+            self.root.as_deref().map(|node| Step::Node { node, prefix: String::new(), side: Side::Root }).into_iter().collect();
+
+        while let Some(step) = steps.pop() {
+            let (node, prefix, side) = match step {
+                Step::Line(line) => {
+                    out.push_str(&line);
+                    out.push('\n');
+                    continue;
+                }
+                Step::Node { node, prefix, side } => (node, prefix, side),
+            };
+
+            // Children of a node drawn above its parent continue the parent's
+            // vertical line below them, and the other way round.
+            let (above, below) = match side {
+                Side::Root => ("", ""),
+                Side::Right => ("    ", "│   "),
+                Side::Left => ("│   ", "    "),
+            };
+            let connector = match side {
+                Side::Root => "",
+                Side::Right => "┌── ",
+                Side::Left => "└── ",
+            };
+
+            if let Some(left) = node.left.as_deref() {
+                steps.push(Step::Node { node: left, prefix: format!("{}{}", prefix, below), side: Side::Left });
+            }
+            steps.push(Step::Line(format!("{}{}{}", prefix, connector, label(&node.key, &node.value))));
+            if let Some(right) = node.right.as_deref() {
+                steps.push(Step::Node { node: right, prefix: format!("{}{}", prefix, above), side: Side::Right });
+            }
+        }
+
+        out
+    }
+
+    /// Graphviz source for the tree, e.g. for `dot -Tsvg`.
+    ///
+    /// A node with one child gets an invisible placeholder for the other,
+    /// so left and right children are drawn on the correct side.
+    pub fn to_dot(&self) -> String
+    where
+        K: Display,
+    {
+        self.to_dot_with(|key, _| key.to_string())
+    }
+
+    pub fn to_dot_with<F: Fn(&K, &V) -> String>(&self, label: F) -> String {
+        let mut out = String::from("digraph BinaryTree {\n    node [shape=circle];\n");
+        let mut stack: Vec<(&Node<K, V>, usize)> = self.root.as_deref().map(|node| (node, 0)).into_iter().collect();
+        let mut next_id = 1;
+
+        while let Some((node, id)) = stack.pop() {
+            let _ = writeln!(out, "    n{} [label=\"{}\"];", id, escape(&label(&node.key, &node.value)));
+
+            if node.left.is_none() && node.right.is_none() {
+                continue;
+            }
+
+            let mut children = Vec::with_capacity(2);
+            for (child, side) in [(node.left.as_deref(), "l"), (node.right.as_deref(), "r")] {
+                match child {
+                    Some(child) => {
+                        let _ = writeln!(out, "    n{} -> n{};", id, next_id);
+                        children.push((child, next_id));
+                        next_id += 1;
+                    }
+                    None => {
+                        let _ = writeln!(out, "    n{}{} [style=invis];", id, side);
+                        let _ = writeln!(out, "    n{} -> n{}{} [style=invis];", id, id, side);
+                    }
+                }
+            }
+            // Left first in the output, so it has to come off the stack first.
+            stack.extend(children.into_iter().rev());
+        }
+
+        out.push_str("}\n");
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{assert_snapshot, sample};
+
+    #[test]
+    fn should_draw_tree_sideways() {
+        assert_snapshot("sample_tree.txt", &sample().to_ascii());
+    }
+
+    #[test]
+    fn should_export_dot() {
+        assert_snapshot("sample_tree.dot", &sample().to_dot());
+    }
+
+    #[test]
+    fn should_label_with_values() {
+        let ages: BinaryTree<&str, u32> = [("grace", 85), ("ada", 36), ("alan", 41)].into_iter().collect();
+
+        assert_eq!(ages.to_ascii_with(|name, age| format!("{} ({})", name, age)), "grace (85)\n│   ┌── alan (41)\n└── ada (36)\n");
+        assert!(ages.to_dot_with(|name, _| format!("\"{}\"", name)).contains(r#"n0 [label="\"grace\""];"#));
+    }
+
+    #[test]
+    fn should_draw_empty_and_degenerate_trees() {
+        let empty: BinaryTree<u32> = BinaryTree::new();
+        let list: BinaryTree<u32> = (0..2_000).collect();
+
+        assert_eq!(empty.to_ascii(), "");
+        assert_eq!(empty.to_dot(), "digraph BinaryTree {\n    node [shape=circle];\n}\n");
+        assert_eq!(list.to_ascii().lines().count(), 2_000);
+        assert!(list.to_dot().contains("n1998 -> n1999;"));
+    }
+}
@@ -0,0 +1,208 @@
+//! A buffer that shares its items between clones until one of them writes.
+//!
+//! Cloning a [`CowBuffer`] only bumps a reference count. The first write
+//! through a clone that is still shared copies the items, so the other
+//! clones never see it; writes to a buffer nobody else shares cost nothing
+//! extra.
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::rc::Rc;
+use std::slice;
+use std::vec;
+
+pub struct CowBuffer<T> {
+    items: Rc<Vec<T>>,
+}
+
+impl<T> CowBuffer<T> {
+    pub fn new() -> CowBuffer<T> {
+        CowBuffer { items: Rc::new(Vec::new()) }
+    }
+
+    /// Whether another clone still shares these items.
+    pub fn is_shared(&self) -> bool {
+        Rc::strong_count(&self.items) > 1
+    }
+
+    /// Whether both buffers share the same items.
+    pub fn ptr_eq(&self, other: &CowBuffer<T>) -> bool {
+        Rc::ptr_eq(&self.items, &other.items)
+    }
+
+    /// Empties this buffer without copying anything, even when shared.
+    pub fn clear(&mut self) {
+        match Rc::get_mut(&mut self.items) {
+            Some(items) => items.clear(),
+            None => self.items = Rc::new(Vec::new()),
+        }
+    }
+}
+
+impl<T: Clone> CowBuffer<T> {
+    /// The items for writing, copied first if they are shared.
+    pub fn to_mut(&mut self) -> &mut Vec<T> {
+        Rc::make_mut(&mut self.items)
+    }
+
+    pub fn push(&mut self, value: T) {
+        self.to_mut().push(value);
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        if self.is_empty() {
+            return None;
+        }
+        self.to_mut().pop()
+    }
+
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.to_mut().truncate(len);
+        }
+    }
+
+    /// The items, copied only if another clone shares them.
+    pub fn into_vec(self) -> Vec<T> {
+        Rc::unwrap_or_clone(self.items)
+    }
+}
+
+impl<T> Clone for CowBuffer<T> {
+    fn clone(&self) -> CowBuffer<T> {
+        CowBuffer { items: Rc::clone(&self.items) }
+    }
+}
+
+impl<T> Default for CowBuffer<T> {
+    fn default() -> CowBuffer<T> {
+        CowBuffer::new()
+    }
+}
+
+impl<T> From<Vec<T>> for CowBuffer<T> {
+    fn from(items: Vec<T>) -> CowBuffer<T> {
+        CowBuffer { items: Rc::new(items) }
+    }
+}
+
+impl<T> Deref for CowBuffer<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.items
+    }
+}
+
+impl<T: Clone> DerefMut for CowBuffer<T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        self.to_mut()
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for CowBuffer<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq> PartialEq for CowBuffer<T> {
+    fn eq(&self, other: &CowBuffer<T>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq> Eq for CowBuffer<T> {}
+
+impl<T> FromIterator<T> for CowBuffer<T> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> CowBuffer<T> {
+        CowBuffer::from(iter.into_iter().collect::<Vec<T>>())
+    }
+}
+
+impl<T: Clone> Extend<T> for CowBuffer<T> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        self.to_mut().extend(iter);
+    }
+}
+
+impl<'a, T> IntoIterator for &'a CowBuffer<T> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T: Clone> IntoIterator for &'a mut CowBuffer<T> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> slice::IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+impl<T: Clone> IntoIterator for CowBuffer<T> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    fn into_iter(self) -> vec::IntoIter<T> {
+        self.into_vec().into_iter()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn should_share_until_first_write() {
+        let original: CowBuffer<u32> = (1..=3).collect();
+        let mut copy = original.clone();
+
+        assert!(copy.ptr_eq(&original));
+        assert!(original.is_shared());
+
+        copy.push(4);
+        assert!(!copy.ptr_eq(&original));
+        assert_eq!(*original, [1, 2, 3]);
+        assert_eq!(*copy, [1, 2, 3, 4]);
+
+        // No longer shared, so this writes in place.
+        let before = copy.as_ptr();
+        copy[0] = 10;
+        assert_eq!(copy.as_ptr(), before);
+    }
+
+    #[test]
+    fn should_not_copy_for_reads_or_no_op_writes() {
+        let original = CowBuffer::from(vec!['a', 'b']);
+        let mut copy = original.clone();
+
+        assert_eq!(copy.iter().collect::<String>(), "ab");
+        copy.truncate(5);
+        assert!(copy.ptr_eq(&original));
+        copy.clear();
+        assert!(copy.is_empty());
+        assert_eq!(copy.pop(), None);
+        assert_eq!(original.len(), 2);
+    }
+
+    #[test]
+    fn should_iterate_every_way() {
+        let mut buffer = CowBuffer::from(vec![String::from("a"), String::from("b")]);
+        let snapshot = buffer.clone();
+
+        for item in &mut buffer {
+            item.push('!');
+        }
+        buffer.extend([String::from("c")]);
+
+        assert_eq!((&snapshot).into_iter().cloned().collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(buffer.clone().into_iter().rev().collect::<Vec<_>>(), ["c", "b!", "a!"]);
+        assert_eq!(snapshot, CowBuffer::from(vec![String::from("a"), String::from("b")]));
+        assert_eq!(format!("{:?}", snapshot), r#"["a", "b"]"#);
+    }
+}
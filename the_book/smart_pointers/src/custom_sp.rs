@@ -1,28 +1,333 @@
-use std::ops::{Deref, DerefMut};
+//! A vector wrapper that owns the only way to change its contents.
+//!
+//! Reads go through `Deref` to a slice, but every write goes through a
+//! method, which lets [`CustomSP`] cap its length, count accesses and tell
+//! observers what changed.
 
+use std::cell::Cell;
+use std::collections::VecDeque;
+use std::error::Error;
+use std::fmt;
+use std::ops::Deref;
 
-pub struct CustomSP<T> {
-    data: Vec<T>
+/// What a push does when the vector is already at its limit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Overflow {
+    /// Refuse the new item and hand it back.
+    #[default]
+    Reject,
+    /// Drop the oldest item to make room, like a ring buffer.
+    DropOldest,
+}
+
+/// A change, passed to every observer after it has happened.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change<'a, T> {
+    Pushed { index: usize },
+    /// The oldest item made room for a push, just before the push.
+    Evicted(&'a T),
+    Rejected(&'a T),
+    Popped(&'a T),
+    Updated { index: usize },
+    Cleared,
+}
+
+/// The item a full vector with [`Overflow::Reject`] refused.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Full<T>(pub T);
+
+impl<T> fmt::Display for Full<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "vector is full")
+    }
+}
+
+impl<T: fmt::Debug> Error for Full<T> {}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    /// Calls to `get` and dereferences to the slice.
+    pub reads: usize,
+    /// Pushes, pops, updates and clears that went through.
+    pub writes: usize,
+    pub evictions: usize,
+    pub rejections: usize,
 }
 
+/// Returned by [`CustomSP::observe`] to remove the observer again.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObserverId(usize);
+
+type Observer<T> = Box<dyn FnMut(&Change<T>)>;
+
+pub struct CustomSP<T> {
+    // Always contiguous, so it derefs to one slice; see `push`.
+    data: VecDeque<T>,
+    limit: usize,
+    overflow: Overflow,
+    // Reads only need `&self`.
+    reads: Cell<usize>,
+    stats: Stats,
+    observers: Vec<(ObserverId, Observer<T>)>,
+    next_observer: usize,
+}
 
 impl<T> CustomSP<T> {
+    /// An unbounded vector.
     pub fn new() -> CustomSP<T> {
-        CustomSP { data: vec![] }
+        CustomSP::bounded(usize::MAX, Overflow::Reject)
+    }
+
+    /// A vector of at most `limit` items.
+    ///
+    /// Panics if `limit` is zero.
+    pub fn bounded(limit: usize, overflow: Overflow) -> CustomSP<T> {
+        assert!(limit > 0, "limit must be at least 1");
+        CustomSP {
+            data: VecDeque::new(),
+            limit,
+            overflow,
+            reads: Cell::new(0),
+            stats: Stats::default(),
+            observers: Vec::new(),
+            next_observer: 0,
+        }
+    }
+
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    pub fn overflow(&self) -> Overflow {
+        self.overflow
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.data.len() == self.limit
+    }
+
+    fn notify(&mut self, change: Change<T>) {
+        for (_, observer) in &mut self.observers {
+            observer(&change);
+        }
+    }
+
+    /// Appends `value`. At the limit, [`Overflow::DropOldest`] returns the
+    /// evicted item and [`Overflow::Reject`] returns `value` as the error.
+    pub fn push(&mut self, value: T) -> Result<Option<T>, Full<T>> {
+        let mut evicted = None;
+
+        if self.is_full() {
+            match self.overflow {
+                Overflow::Reject => {
+                    self.stats.rejections += 1;
+                    self.notify(Change::Rejected(&value));
+                    return Err(Full(value));
+                }
+                Overflow::DropOldest => {
+                    // With room for twice the limit, the items only wrap
+                    // around the end of the buffer once every `limit`
+                    // evictions, so the rotation below is O(1) amortized.
+                    if self.data.capacity() - self.data.len() < self.limit {
+                        self.data.reserve_exact(self.limit);
+                    }
+                    let oldest = self.data.pop_front().expect("a full vector has items");
+                    self.stats.evictions += 1;
+                    self.notify(Change::Evicted(&oldest));
+                    evicted = Some(oldest);
+                }
+            }
+        }
+
+        self.data.push_back(value);
+        self.data.make_contiguous();
+        self.stats.writes += 1;
+        self.notify(Change::Pushed { index: self.data.len() - 1 });
+        Ok(evicted)
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        let value = self.data.pop_back()?;
+        self.stats.writes += 1;
+        self.notify(Change::Popped(&value));
+        Some(value)
+    }
+
+    pub fn get(&self, index: usize) -> Option<&T> {
+        self.reads.set(self.reads.get() + 1);
+        self.data.get(index)
+    }
+
+    /// Changes the item at `index` in place, returning `false` if there is
+    /// none.
+    pub fn update<F: FnOnce(&mut T)>(&mut self, index: usize, f: F) -> bool {
+        let Some(item) = self.data.get_mut(index) else {
+            return false;
+        };
+        f(item);
+        self.stats.writes += 1;
+        self.notify(Change::Updated { index });
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.data.clear();
+        self.stats.writes += 1;
+        self.notify(Change::Cleared);
+    }
+
+    pub fn stats(&self) -> Stats {
+        Stats { reads: self.reads.get(), ..self.stats }
+    }
+
+    pub fn reset_stats(&mut self) {
+        self.reads.set(0);
+        self.stats = Stats::default();
+    }
+
+    /// Calls `observer` after every change, in the order observers were
+    /// added.
+    pub fn observe<F: FnMut(&Change<T>) + 'static>(&mut self, observer: F) -> ObserverId {
+        let id = ObserverId(self.next_observer);
+        self.next_observer += 1;
+        self.observers.push((id, Box::new(observer)));
+        id
+    }
+
+    /// Removes an observer, returning whether it was still registered.
+    pub fn unobserve(&mut self, id: ObserverId) -> bool {
+        let before = self.observers.len();
+        self.observers.retain(|(observer, _)| *observer != id);
+        self.observers.len() != before
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.data.into()
     }
 }
 
-impl<T> Deref for CustomSP<T>  {
-    type Target = Vec<T>;
+impl<T> Default for CustomSP<T> {
+    fn default() -> CustomSP<T> {
+        CustomSP::new()
+    }
+}
 
-    fn deref(&self) -> &Self::Target {
-        &self.data
+impl<T> Deref for CustomSP<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        self.reads.set(self.reads.get() + 1);
+        let (items, wrapped) = self.data.as_slices();
+        debug_assert!(wrapped.is_empty(), "writes keep the items contiguous");
+        items
     }
 }
 
-impl<T> DerefMut for CustomSP<T>  {
-    
-    fn deref_mut(&mut self) -> &mut Self::Target {
-            &mut self.data
+impl<T: fmt::Debug> fmt::Debug for CustomSP<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("CustomSP")
+            .field("data", &self.data)
+            .field("limit", &self.limit)
+            .field("overflow", &self.overflow)
+            .field("observers", &self.observers.len())
+            .finish()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    #[test]
+    fn should_reject_when_full() {
+        let mut items = CustomSP::bounded(2, Overflow::Reject);
+
+        assert_eq!(items.push(1), Ok(None));
+        assert_eq!(items.push(2), Ok(None));
+        assert_eq!(items.push(3), Err(Full(3)));
+        assert_eq!(*items, [1, 2]);
+        assert!(items.is_full());
+    }
+
+    #[test]
+    fn should_drop_oldest_like_a_ring_buffer() {
+        let mut items = CustomSP::bounded(3, Overflow::DropOldest);
+
+        for i in 0..5 {
+            items.push(i).unwrap();
+        }
+
+        assert_eq!(*items, [2, 3, 4]);
+        assert_eq!(items.push(5), Ok(Some(2)));
+        assert_eq!(items.iter().sum::<i32>(), 12);
+        assert_eq!(items.first(), Some(&3));
+    }
+
+    #[test]
+    fn should_stay_one_slice_while_evicting() {
+        let mut items = CustomSP::bounded(5, Overflow::DropOldest);
+
+        for i in 0..100u32 {
+            let evicted = items.push(i).unwrap();
+            assert_eq!(evicted, i.checked_sub(5));
+            let start = i.saturating_sub(4);
+            assert!(items.iter().copied().eq(start..=i));
+        }
+        items.pop();
+        assert_eq!(items.into_vec(), [95, 96, 97, 98]);
+    }
+
+    #[test]
+    fn should_count_reads_and_writes() {
+        let mut items = CustomSP::bounded(1, Overflow::DropOldest);
+        items.push('a').unwrap();
+        items.push('b').unwrap();
+        items.update(0, |c| *c = 'c');
+        items.update(5, |c| *c = 'x');
+
+        assert_eq!(items.get(0), Some(&'c'));
+        assert_eq!(items.len(), 1);
+        assert_eq!(items.stats(), Stats { reads: 2, writes: 3, evictions: 1, rejections: 0 });
+        items.reset_stats();
+        assert_eq!(items.stats(), Stats::default());
+    }
+
+    #[test]
+    fn should_notify_observers_in_order() {
+        let log = Rc::new(RefCell::new(Vec::new()));
+        let mut items = CustomSP::bounded(2, Overflow::DropOldest);
+
+        let first = {
+            let log = Rc::clone(&log);
+            items.observe(move |change: &Change<u8>| log.borrow_mut().push(format!("first {:?}", change)))
+        };
+        {
+            let log = Rc::clone(&log);
+            items.observe(move |change: &Change<u8>| log.borrow_mut().push(format!("second {:?}", change)));
+        }
+
+        items.push(1).unwrap();
+        assert!(items.unobserve(first));
+        assert!(!items.unobserve(first));
+        items.push(2).unwrap();
+        items.push(3).unwrap();
+        items.pop();
+        items.clear();
+
+        assert_eq!(
+            *log.borrow(),
+            [
+                "first Pushed { index: 0 }",
+                "second Pushed { index: 0 }",
+                "second Pushed { index: 1 }",
+                "second Evicted(1)",
+                "second Pushed { index: 1 }",
+                "second Popped(3)",
+                "second Cleared",
+            ]
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use std::fs::{File, OpenOptions};
+use std::io::{self, BufWriter, Write};
+use std::path::{Path, PathBuf};
 
 pub struct MockFile {
     file_name: String,
@@ -16,4 +19,174 @@ impl Drop for MockFile {
     }
 }
 
+type ErrorHandler = Box<dyn FnOnce(&Path, io::Error)>;
 
+/// What [`MockFile`] pretends to be: a real file that buffers writes and,
+/// when dropped, flushes them and waits until they are on disk.
+///
+/// `Drop` cannot return an error, so a failure while closing on drop goes to
+/// the handler set with [`on_error`](GuardedFile::on_error), or to stderr if
+/// there is none. Call [`close`](GuardedFile::close) to get it as a `Result`
+/// instead.
+pub struct GuardedFile {
+    path: PathBuf,
+    // `None` once closed.
+    writer: Option<BufWriter<File>>,
+    on_error: Option<ErrorHandler>,
+}
+
+impl GuardedFile {
+    /// Creates the file, or empties it if it exists.
+    pub fn create<P: AsRef<Path>>(path: P) -> io::Result<GuardedFile> {
+        GuardedFile::open_with(path.as_ref(), OpenOptions::new().write(true).create(true).truncate(true))
+    }
+
+    /// Opens the file for writing at its end, creating it if needed.
+    pub fn append<P: AsRef<Path>>(path: P) -> io::Result<GuardedFile> {
+        GuardedFile::open_with(path.as_ref(), OpenOptions::new().append(true).create(true))
+    }
+
+    fn open_with(path: &Path, options: &OpenOptions) -> io::Result<GuardedFile> {
+        let file = options.open(path)?;
+        Ok(GuardedFile { path: path.to_path_buf(), writer: Some(BufWriter::new(file)), on_error: None })
+    }
+
+    /// Calls `handler` instead of printing if closing on drop fails.
+    pub fn on_error<F: FnOnce(&Path, io::Error) + 'static>(mut self, handler: F) -> GuardedFile {
+        self.on_error = Some(Box::new(handler));
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    fn writer(&mut self) -> &mut BufWriter<File> {
+        // Only `close` and `drop` take the writer, and both consume `self`.
+        self.writer.as_mut().expect("file already closed")
+    }
+
+    /// Flushes the buffer and waits until the operating system has written
+    /// everything to disk.
+    pub fn sync(&mut self) -> io::Result<()> {
+        let writer = self.writer();
+        writer.flush()?;
+        writer.get_ref().sync_all()
+    }
+
+    fn finish(&mut self) -> io::Result<()> {
+        let Some(mut writer) = self.writer.take() else {
+            return Ok(());
+        };
+
+        // Dropping a `BufWriter` that still holds data flushes it again, so
+        // after a failed flush it is taken apart, discarding the data, to
+        // keep that second attempt from happening unreported.
+        let flushed = writer.flush();
+        let (file, _) = writer.into_parts();
+        flushed?;
+        file.sync_all()
+    }
+
+    /// Flushes, syncs and closes the file, reporting any failure.
+    pub fn close(mut self) -> io::Result<()> {
+        self.finish()
+    }
+}
+
+impl Write for GuardedFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.writer().write(buf)
+    }
+
+    /// Hands buffered data to the operating system without waiting for it to
+    /// reach the disk; see [`sync`](GuardedFile::sync) for that.
+    fn flush(&mut self) -> io::Result<()> {
+        self.writer().flush()
+    }
+}
+
+impl Drop for GuardedFile {
+    fn drop(&mut self) {
+        if let Err(e) = self.finish() {
+            match self.on_error.take() {
+                Some(handler) => handler(&self.path, e),
+                None => eprintln!("Failed to close file {}: {}", self.path.display(), e),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::env;
+    use std::fs;
+    use std::process;
+    use std::rc::Rc;
+
+    use super::*;
+
+    // A path in the temp directory that no other test or test run uses.
+    fn temp_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("smart_pointers_{}_{}", process::id(), name))
+    }
+
+    #[test]
+    fn should_write_to_disk_when_dropped() {
+        let path = temp_path("dropped.txt");
+
+        {
+            let mut file = GuardedFile::create(&path).unwrap();
+            write!(file, "hello").unwrap();
+            writeln!(file, ", world").unwrap();
+
+            // Still in the buffer.
+            assert_eq!(fs::read_to_string(&path).unwrap(), "");
+        }
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "hello, world\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_append_and_close_explicitly() {
+        let path = temp_path("appended.txt");
+        fs::write(&path, "first\n").unwrap();
+
+        let mut file = GuardedFile::append(&path).unwrap();
+        file.write_all(b"second\n").unwrap();
+        file.sync().unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\n");
+        file.write_all(b"third\n").unwrap();
+
+        assert!(file.close().is_ok());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "first\nsecond\nthird\n");
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn should_fail_to_open_missing_directory() {
+        assert!(GuardedFile::create(temp_path("missing").join("file.txt")).is_err());
+    }
+
+    // Writes to /dev/full always fail with "no space left on device".
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn should_report_flush_errors() {
+        let mut file = GuardedFile::create("/dev/full").unwrap();
+        file.write_all(b"lost").unwrap();
+        assert_eq!(file.close().unwrap_err().kind(), io::ErrorKind::StorageFull);
+
+        let reported = Rc::new(RefCell::new(None));
+        {
+            let reported = Rc::clone(&reported);
+            let mut file = GuardedFile::create("/dev/full")
+                .unwrap()
+                .on_error(move |path, e| *reported.borrow_mut() = Some((path.to_path_buf(), e.kind())));
+            file.write_all(b"lost").unwrap();
+        }
+
+        assert_eq!(*reported.borrow(), Some((PathBuf::from("/dev/full"), io::ErrorKind::StorageFull)));
+    }
+}
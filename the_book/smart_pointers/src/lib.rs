@@ -0,0 +1,12 @@
+pub mod arena_graph;
+pub mod avl_tree;
+pub mod binary_tree;
+pub mod cow_buffer;
+pub mod custom_sp;
+pub mod dropping;
+pub mod reference_counting;
+pub mod scope_guard;
+pub mod small_vec;
+
+#[cfg(test)]
+mod test_util;
@@ -1,13 +1,13 @@
-pub mod binary_tree;
-pub mod custom_sp;
-pub mod dropping;
-pub mod reference_counting;
+use smart_pointers::binary_tree;
+use smart_pointers::reference_counting::Graph;
+
 fn main() {
     println!("Hello, world!");
 
 
-    let mut tree = binary_tree::BinaryTree::new(1);
+    let mut tree = binary_tree::BinaryTree::new();
 
+    tree.insert_value(1);
     tree.insert_value(10);
     tree.insert_value(5);
     tree.insert_value(6);
@@ -17,5 +17,16 @@ fn main() {
     tree.insert_value(18);
 
 
-    println!("{:?}", tree);
+    print!("{}", tree.to_ascii());
+
+    let root = Graph::new(1);
+    let shared = Graph::new(4);
+    for value in [2, 3] {
+        let child = Graph::new(value);
+        child.add_child(&shared).unwrap();
+        root.add_child(&child).unwrap();
+    }
+
+    let order: Vec<u32> = root.bfs().map(|node| *node.value()).collect();
+    println!("{:?}, 4 has {} parents", order, shared.parents().len());
 }
@@ -1,22 +1,360 @@
-use std::rc::Rc;
+//! A directed acyclic graph of shared nodes.
+//!
+//! A [`Graph`] is a handle to one node. Cloning it clones the `Rc`, not the
+//! node, so every handle sees the same value and edges. Parents own their
+//! children through strong references and children point back at their
+//! parents through `Weak` ones, so a node lives exactly as long as something
+//! outside the graph or one of its parents holds on to it. Edges that would
+//! close a cycle are refused, because a cycle of strong references would
+//! never be freed.
 
-pub struct Graph {
-    value: u32,
-    children: Vec<Rc<Graph>>
+use std::cell::{Ref, RefCell, RefMut};
+use std::collections::{HashSet, VecDeque};
+use std::error::Error;
+use std::fmt;
+use std::rc::{Rc, Weak};
+
+struct Node<T> {
+    value: T,
+    children: Vec<Rc<RefCell<Node<T>>>>,
+    parents: Vec<Weak<RefCell<Node<T>>>>,
 }
 
-impl Graph {
-    pub fn new(value: u32) -> Graph {
-        Graph { value, children: vec![] }
+// Dropping the children in place would recurse once per level of a long
+// chain, so children whose last owner is this node are unlinked one at a
+// time instead. Children someone else still owns are left to them.
+impl<T> Drop for Node<T> {
+    fn drop(&mut self) {
+        let mut stack = std::mem::take(&mut self.children);
+
+        while let Some(child) = stack.pop() {
+            if let Ok(child) = Rc::try_unwrap(child) {
+                stack.append(&mut child.into_inner().children);
+            }
+        }
     }
+}
 
+pub struct Graph<T = u32> {
+    node: Rc<RefCell<Node<T>>>,
+}
 
+/// A handle that does not keep its node alive.
+pub struct WeakGraph<T = u32> {
+    node: Weak<RefCell<Node<T>>>,
+}
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EdgeError {
+    /// The child is already a child of this parent.
+    Duplicate,
+    /// The child is the parent itself or one of its ancestors.
+    Cycle,
 }
 
-// fn main_rc() {
-//     let child = Graph::new(10);
-//     let graph1 = Graph {value: 12, children: }
+impl fmt::Display for EdgeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EdgeError::Duplicate => write!(f, "edge already exists"),
+            EdgeError::Cycle => write!(f, "edge would create a cycle"),
+        }
+    }
+}
 
-//     let 
-// }
\ No newline at end of file
+impl Error for EdgeError {}
+
+// Identity of a node for the visited sets of the traversals.
+fn address<T>(node: &Rc<RefCell<Node<T>>>) -> *const RefCell<Node<T>> {
+    Rc::as_ptr(node)
+}
+
+impl<T> Graph<T> {
+    pub fn new(value: T) -> Graph<T> {
+        Graph { node: Rc::new(RefCell::new(Node { value, children: vec![], parents: vec![] })) }
+    }
+
+    fn from_rc(node: Rc<RefCell<Node<T>>>) -> Graph<T> {
+        Graph { node }
+    }
+
+    /// Panics if the value is currently borrowed through
+    /// [`value_mut`](Graph::value_mut).
+    pub fn value(&self) -> Ref<'_, T> {
+        Ref::map(self.node.borrow(), |node| &node.value)
+    }
+
+    pub fn value_mut(&self) -> RefMut<'_, T> {
+        RefMut::map(self.node.borrow_mut(), |node| &mut node.value)
+    }
+
+    /// Replaces the value, returning the old one.
+    pub fn set_value(&self, value: T) -> T {
+        std::mem::replace(&mut self.node.borrow_mut().value, value)
+    }
+
+    pub fn children(&self) -> Vec<Graph<T>> {
+        self.node.borrow().children.iter().cloned().map(Graph::from_rc).collect()
+    }
+
+    /// The parents that are still alive.
+    pub fn parents(&self) -> Vec<Graph<T>> {
+        self.node.borrow().parents.iter().filter_map(Weak::upgrade).map(Graph::from_rc).collect()
+    }
+
+    pub fn has_child(&self, child: &Graph<T>) -> bool {
+        self.node.borrow().children.iter().any(|c| Rc::ptr_eq(c, &child.node))
+    }
+
+    /// Adds an edge from `self` to `child`.
+    pub fn add_child(&self, child: &Graph<T>) -> Result<(), EdgeError> {
+        if self.has_child(child) {
+            return Err(EdgeError::Duplicate);
+        }
+        if child.dfs().any(|node| node.ptr_eq(self)) {
+            return Err(EdgeError::Cycle);
+        }
+
+        self.node.borrow_mut().children.push(Rc::clone(&child.node));
+        let mut child = child.node.borrow_mut();
+        child.parents.retain(|parent| parent.strong_count() > 0);
+        child.parents.push(Rc::downgrade(&self.node));
+        Ok(())
+    }
+
+    /// Removes the edge from `self` to `child`, returning whether there was
+    /// one. The child is freed if that edge was its last owner.
+    pub fn remove_child(&self, child: &Graph<T>) -> bool {
+        let removed = {
+            let mut node = self.node.borrow_mut();
+            let before = node.children.len();
+            node.children.retain(|c| !Rc::ptr_eq(c, &child.node));
+            node.children.len() != before
+        };
+
+        if removed {
+            let parent = Rc::downgrade(&self.node);
+            child.node.borrow_mut().parents.retain(|p| !p.ptr_eq(&parent) && p.strong_count() > 0);
+        }
+        removed
+    }
+
+    /// Whether both handles point at the same node.
+    pub fn ptr_eq(&self, other: &Graph<T>) -> bool {
+        Rc::ptr_eq(&self.node, &other.node)
+    }
+
+    // An address identifying the node, for maps keyed on nodes.
+    pub(crate) fn id(&self) -> usize {
+        address(&self.node) as usize
+    }
+
+    pub fn downgrade(&self) -> WeakGraph<T> {
+        WeakGraph { node: Rc::downgrade(&self.node) }
+    }
+
+    /// Handles to this node, counting the parents that own it.
+    pub fn strong_count(&self) -> usize {
+        Rc::strong_count(&self.node)
+    }
+
+    /// This node and everything reachable from it, depth first with children
+    /// in the order they were added. Nodes shared by several parents are
+    /// visited once.
+    pub fn dfs(&self) -> Dfs<T> {
+        Dfs { stack: vec![Rc::clone(&self.node)], visited: HashSet::new() }
+    }
+
+    /// Like [`dfs`](Graph::dfs), but breadth first.
+    pub fn bfs(&self) -> Bfs<T> {
+        let mut visited = HashSet::new();
+        visited.insert(address(&self.node));
+        Bfs { queue: VecDeque::from([Rc::clone(&self.node)]), visited }
+    }
+}
+
+impl<T> Clone for Graph<T> {
+    fn clone(&self) -> Graph<T> {
+        Graph { node: Rc::clone(&self.node) }
+    }
+}
+
+impl<T: fmt::Debug> fmt::Debug for Graph<T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let node = self.node.borrow();
+        f.debug_struct("Graph").field("value", &node.value).field("children", &node.children.len()).finish()
+    }
+}
+
+impl<T> WeakGraph<T> {
+    /// The node, or `None` once it has been freed.
+    pub fn upgrade(&self) -> Option<Graph<T>> {
+        self.node.upgrade().map(Graph::from_rc)
+    }
+}
+
+impl<T> Clone for WeakGraph<T> {
+    fn clone(&self) -> WeakGraph<T> {
+        WeakGraph { node: Weak::clone(&self.node) }
+    }
+}
+
+pub struct Dfs<T> {
+    stack: Vec<Rc<RefCell<Node<T>>>>,
+    visited: HashSet<*const RefCell<Node<T>>>,
+}
+
+impl<T> Iterator for Dfs<T> {
+    type Item = Graph<T>;
+
+    fn next(&mut self) -> Option<Graph<T>> {
+        while let Some(node) = self.stack.pop() {
+            if !self.visited.insert(address(&node)) {
+                continue;
+            }
+            // Pushed in reverse so the first child comes off the stack first.
+            self.stack.extend(node.borrow().children.iter().rev().cloned());
+            return Some(Graph::from_rc(node));
+        }
+        None
+    }
+}
+
+pub struct Bfs<T> {
+    queue: VecDeque<Rc<RefCell<Node<T>>>>,
+    visited: HashSet<*const RefCell<Node<T>>>,
+}
+
+impl<T> Iterator for Bfs<T> {
+    type Item = Graph<T>;
+
+    fn next(&mut self) -> Option<Graph<T>> {
+        let node = self.queue.pop_front()?;
+        for child in node.borrow().children.iter() {
+            if self.visited.insert(address(child)) {
+                self.queue.push_back(Rc::clone(child));
+            }
+        }
+        Some(Graph::from_rc(node))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use super::*;
+
+    fn values(nodes: impl Iterator<Item = Graph<u32>>) -> Vec<u32> {
+        nodes.map(|node| *node.value()).collect()
+    }
+
+    //     1
+    //    / \
+    //   2   3
+    //  / \ /
+    // 4   5
+    fn diamond() -> (Graph, Vec<Graph>) {
+        let nodes: Vec<Graph> = (1..=5).map(Graph::new).collect();
+        for (parent, child) in [(0, 1), (0, 2), (1, 3), (1, 4), (2, 4)] {
+            nodes[parent].add_child(&nodes[child]).unwrap();
+        }
+        (nodes[0].clone(), nodes)
+    }
+
+    #[test]
+    fn should_traverse_shared_nodes_once() {
+        let (root, _nodes) = diamond();
+
+        assert_eq!(values(root.dfs()), vec![1, 2, 4, 5, 3]);
+        assert_eq!(values(root.bfs()), vec![1, 2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn should_link_parents_both_ways() {
+        let (_, nodes) = diamond();
+
+        assert_eq!(values(nodes[4].parents().into_iter()), vec![2, 3]);
+        assert!(nodes[1].has_child(&nodes[4]));
+        assert!(nodes[1].remove_child(&nodes[4]));
+        assert!(!nodes[1].remove_child(&nodes[4]));
+        assert_eq!(values(nodes[4].parents().into_iter()), vec![3]);
+        assert_eq!(values(nodes[1].children().into_iter()), vec![4]);
+    }
+
+    #[test]
+    fn should_refuse_duplicate_and_cyclic_edges() {
+        let (root, nodes) = diamond();
+
+        assert_eq!(root.add_child(&nodes[1]), Err(EdgeError::Duplicate));
+        assert_eq!(nodes[4].add_child(&root), Err(EdgeError::Cycle));
+        assert_eq!(root.add_child(&root), Err(EdgeError::Cycle));
+        assert_eq!(nodes[2].add_child(&nodes[3]), Ok(()));
+    }
+
+    #[test]
+    fn should_share_values_between_handles() {
+        let node = Graph::new(String::from("a"));
+        let other = node.clone();
+
+        other.value_mut().push('b');
+        assert_eq!(node.set_value(String::from("c")), "ab");
+        assert_eq!(*other.value(), "c");
+        assert!(node.ptr_eq(&other));
+        assert_eq!(node.strong_count(), 2);
+    }
+
+    struct Tracked(Rc<Cell<usize>>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    #[test]
+    fn should_free_nodes_when_last_strong_reference_drops() {
+        let dropped = Rc::new(Cell::new(0));
+        let root = Graph::new(Tracked(Rc::clone(&dropped)));
+        let shared = Graph::new(Tracked(Rc::clone(&dropped)));
+        for _ in 0..3 {
+            let child = Graph::new(Tracked(Rc::clone(&dropped)));
+            child.add_child(&shared).unwrap();
+            root.add_child(&child).unwrap();
+        }
+        let watch_shared = shared.downgrade();
+        let watch_root = root.downgrade();
+        drop(shared);
+
+        // Only parents own `shared` now, and only `root` owns them.
+        let first = root.children().remove(0);
+        root.remove_child(&first);
+        assert_eq!(dropped.get(), 0);
+        drop(first);
+        assert_eq!(dropped.get(), 1);
+        assert_eq!(watch_shared.upgrade().unwrap().parents().len(), 2);
+
+        drop(root);
+        assert_eq!(dropped.get(), 5);
+        assert!(watch_root.upgrade().is_none());
+        assert!(watch_shared.upgrade().is_none());
+    }
+
+    #[test]
+    fn should_drop_long_chains_without_recursion() {
+        let dropped = Rc::new(Cell::new(0));
+        let root = Graph::new(Tracked(Rc::clone(&dropped)));
+        let mut last = root.clone();
+        for _ in 0..100_000 {
+            let child = Graph::new(Tracked(Rc::clone(&dropped)));
+            last.add_child(&child).unwrap();
+            last = child;
+        }
+        let tail = last;
+
+        drop(root);
+        assert_eq!(dropped.get(), 100_000);
+        assert!(tail.parents().is_empty());
+        drop(tail);
+        assert_eq!(dropped.get(), 100_001);
+    }
+}
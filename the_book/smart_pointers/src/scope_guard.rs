@@ -0,0 +1,229 @@
+//! Run cleanup code when a scope ends, the way [`MockFile`]'s `Drop` does,
+//! without writing a type for each kind of cleanup.
+//!
+//! [`defer!`](crate::defer) and its variants cover the common case of
+//! running a block at the end of the current scope. [`ScopeGuard`] also owns
+//! a value the cleanup gets at the end, and can be dismissed. Guards run in
+//! reverse order of creation, like any other local variables being dropped.
+//!
+//! [`MockFile`]: crate::dropping::MockFile
+
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::thread;
+
+/// When a guard runs its cleanup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum When {
+    Always,
+    /// Only if the scope ends normally.
+    OnSuccess,
+    /// Only if the scope ends because of a panic.
+    OnUnwind,
+}
+
+impl When {
+    fn applies(self, unwinding: bool) -> bool {
+        match self {
+            When::Always => true,
+            When::OnSuccess => !unwinding,
+            When::OnUnwind => unwinding,
+        }
+    }
+}
+
+/// Holds a `T` and passes it to `cleanup` when dropped.
+///
+/// The guard derefs to the value, so it can stand in for it until then.
+pub struct ScopeGuard<T, F: FnOnce(T)> {
+    // `None` only after `dismiss` or `drop`.
+    inner: Option<(T, F)>,
+    when: When,
+    // A guard made while the thread is already unwinding, e.g. in another
+    // value's `drop`, sees `thread::panicking()` even if its own scope ends
+    // normally, so only a change since creation counts as a panic.
+    panicking_at_creation: bool,
+}
+
+impl<T, F: FnOnce(T)> ScopeGuard<T, F> {
+    pub fn new(value: T, cleanup: F) -> ScopeGuard<T, F> {
+        ScopeGuard::with_when(value, cleanup, When::Always)
+    }
+
+    pub fn on_success(value: T, cleanup: F) -> ScopeGuard<T, F> {
+        ScopeGuard::with_when(value, cleanup, When::OnSuccess)
+    }
+
+    pub fn on_unwind(value: T, cleanup: F) -> ScopeGuard<T, F> {
+        ScopeGuard::with_when(value, cleanup, When::OnUnwind)
+    }
+
+    pub fn with_when(value: T, cleanup: F, when: When) -> ScopeGuard<T, F> {
+        ScopeGuard { inner: Some((value, cleanup)), when, panicking_at_creation: thread::panicking() }
+    }
+
+    pub fn when(&self) -> When {
+        self.when
+    }
+
+    /// Gives back the value without running the cleanup.
+    pub fn dismiss(mut self) -> T {
+        let (value, _) = self.inner.take().unwrap();
+        value
+    }
+}
+
+impl<T, F: FnOnce(T)> Deref for ScopeGuard<T, F> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.inner.as_ref().unwrap().0
+    }
+}
+
+impl<T, F: FnOnce(T)> DerefMut for ScopeGuard<T, F> {
+    fn deref_mut(&mut self) -> &mut T {
+        &mut self.inner.as_mut().unwrap().0
+    }
+}
+
+impl<T, F: FnOnce(T)> Drop for ScopeGuard<T, F> {
+    fn drop(&mut self) {
+        if let Some((value, cleanup)) = self.inner.take()
+            && self.when.applies(thread::panicking() && !self.panicking_at_creation)
+        {
+            cleanup(value);
+        }
+    }
+}
+
+impl<T: fmt::Debug, F: FnOnce(T)> fmt::Debug for ScopeGuard<T, F> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("ScopeGuard").field("value", &**self).field("when", &self.when).finish()
+    }
+}
+
+/// Runs the block when the enclosing scope ends, however it ends.
+///
+/// ```
+/// use smart_pointers::defer;
+///
+/// defer! { println!("second"); }
+/// println!("first");
+/// ```
+#[macro_export]
+macro_rules! defer {
+    ($($body:tt)*) => {
+        let _guard = $crate::scope_guard::ScopeGuard::new((), |()| { $($body)* });
+    };
+}
+
+/// Runs the block when the enclosing scope ends, unless it ends in a panic.
+#[macro_export]
+macro_rules! defer_on_success {
+    ($($body:tt)*) => {
+        let _guard = $crate::scope_guard::ScopeGuard::on_success((), |()| { $($body)* });
+    };
+}
+
+/// Runs the block only if the enclosing scope ends in a panic.
+#[macro_export]
+macro_rules! defer_on_unwind {
+    ($($body:tt)*) => {
+        let _guard = $crate::scope_guard::ScopeGuard::on_unwind((), |()| { $($body)* });
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::panic::{self, AssertUnwindSafe};
+
+    use super::*;
+
+    // Runs `body` with a log to write to, returning the log and whether
+    // `body` panicked.
+    fn run(body: impl FnOnce(&RefCell<Vec<String>>)) -> (Vec<String>, bool) {
+        let log = RefCell::new(Vec::new());
+        let panicked = panic::catch_unwind(AssertUnwindSafe(|| body(&log))).is_err();
+        (log.into_inner(), panicked)
+    }
+
+    #[test]
+    fn should_run_nested_guards_in_reverse_order() {
+        let (log, _) = run(|log| {
+            defer! { log.borrow_mut().push(String::from("outer 1")); }
+            defer! { log.borrow_mut().push(String::from("outer 2")); }
+            {
+                defer! { log.borrow_mut().push(String::from("inner")); }
+                log.borrow_mut().push(String::from("inner body"));
+            }
+            log.borrow_mut().push(String::from("outer body"));
+        });
+
+        assert_eq!(log, ["inner body", "inner", "outer body", "outer 2", "outer 1"]);
+    }
+
+    #[test]
+    fn should_pick_guards_by_how_the_scope_ends() {
+        let body = |fail: bool| {
+            move |log: &RefCell<Vec<String>>| {
+                defer! { log.borrow_mut().push(String::from("always")); }
+                defer_on_success! { log.borrow_mut().push(String::from("success")); }
+                defer_on_unwind! { log.borrow_mut().push(String::from("unwind")); }
+                if fail {
+                    panic!("failed on purpose");
+                }
+            }
+        };
+
+        assert_eq!(run(body(false)), (vec![String::from("success"), String::from("always")], false));
+        assert_eq!(run(body(true)), (vec![String::from("unwind"), String::from("always")], true));
+    }
+
+    #[test]
+    fn should_hand_its_value_to_the_cleanup() {
+        let (log, _) = run(|log| {
+            let mut lines = ScopeGuard::new(Vec::new(), |lines: Vec<&str>| log.borrow_mut().push(lines.join(" ")));
+            lines.push("written");
+            lines.push("on exit");
+            assert_eq!(lines.len(), 2);
+        });
+
+        assert_eq!(log, ["written on exit"]);
+    }
+
+    #[test]
+    fn should_not_run_once_dismissed() {
+        let (log, _) = run(|log| {
+            let rollback = ScopeGuard::on_unwind(7, |n| log.borrow_mut().push(format!("rolled back {}", n)));
+            assert_eq!(rollback.when(), When::OnUnwind);
+            // The transaction committed, so there is nothing to roll back.
+            assert_eq!(rollback.dismiss(), 7);
+            panic!("after commit");
+        });
+
+        assert!(log.is_empty());
+    }
+
+    #[test]
+    fn should_judge_guards_made_during_unwinding_by_their_own_scope() {
+        struct Cleanup<'a>(&'a RefCell<Vec<String>>);
+
+        impl Drop for Cleanup<'_> {
+            fn drop(&mut self) {
+                let log = self.0;
+                defer_on_success! { log.borrow_mut().push(String::from("success")); }
+                defer_on_unwind! { log.borrow_mut().push(String::from("unwind")); }
+            }
+        }
+
+        let (log, panicked) = run(|log| {
+            let _cleanup = Cleanup(log);
+            panic!("failed on purpose");
+        });
+
+        assert!(panicked);
+        assert_eq!(log, ["success"]);
+    }
+}
@@ -0,0 +1,384 @@
+//! A vector that keeps up to `N` items inline and only allocates beyond
+//! that.
+//!
+//! The inline items live in an array of `MaybeUninit<T>` of which the first
+//! `len` are initialized, which takes some `unsafe`. The tests count drops to
+//! check that every item is dropped exactly once, and are small enough to
+//! run under Miri with `cargo +nightly miri test small_vec`.
+
+use std::fmt;
+use std::mem::MaybeUninit;
+use std::ops::{Deref, DerefMut};
+use std::slice;
+use std::vec;
+
+enum Data<T, const N: usize> {
+    // Invariant: `items[..len]` are initialized and `len <= N`.
+    Inline { items: [MaybeUninit<T>; N], len: usize },
+    Heap(Vec<T>),
+}
+
+pub struct SmallVec<T, const N: usize> {
+    data: Data<T, N>,
+}
+
+impl<T, const N: usize> SmallVec<T, N> {
+    pub fn new() -> SmallVec<T, N> {
+        SmallVec { data: Data::Inline { items: [const { MaybeUninit::uninit() }; N], len: 0 } }
+    }
+
+    /// Whether the items have moved to the heap. They stay there even if
+    /// the vector shrinks again.
+    pub fn spilled(&self) -> bool {
+        matches!(self.data, Data::Heap(_))
+    }
+
+    pub fn capacity(&self) -> usize {
+        match &self.data {
+            Data::Inline { .. } => N,
+            Data::Heap(items) => items.capacity(),
+        }
+    }
+
+    pub fn push(&mut self, value: T) {
+        match &mut self.data {
+            Data::Inline { items, len } if *len < N => {
+                items[*len].write(value);
+                *len += 1;
+            }
+            Data::Inline { items, len } => {
+                let mut heap = Vec::with_capacity((N * 2).max(4));
+                let count = *len;
+                // Zero first, so the items count as moved even if the
+                // pushes below panic.
+                *len = 0;
+                for item in &items[..count] {
+                    // SAFETY: `item` is one of the first `count` items, which
+                    // were initialized, and it is read exactly once.
+                    heap.push(unsafe { item.assume_init_read() });
+                }
+                heap.push(value);
+                self.data = Data::Heap(heap);
+            }
+            Data::Heap(items) => items.push(value),
+        }
+    }
+
+    pub fn pop(&mut self) -> Option<T> {
+        match &mut self.data {
+            Data::Inline { len: 0, .. } => None,
+            Data::Inline { items, len } => {
+                *len -= 1;
+                // SAFETY: the item was below the old `len`, so it is
+                // initialized, and it is now outside `len` so nothing else
+                // will read or drop it.
+                Some(unsafe { items[*len].assume_init_read() })
+            }
+            Data::Heap(items) => items.pop(),
+        }
+    }
+
+    pub fn truncate(&mut self, new_len: usize) {
+        match &mut self.data {
+            Data::Inline { items, len } => {
+                while *len > new_len {
+                    *len -= 1;
+                    // SAFETY: as in `pop`, and `len` is lowered first so a
+                    // panicking drop cannot lead to a second drop.
+                    unsafe { items[*len].assume_init_drop() };
+                }
+            }
+            Data::Heap(items) => items.truncate(new_len),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.truncate(0);
+    }
+
+    pub fn into_vec(self) -> Vec<T> {
+        self.into_iter().collect()
+    }
+}
+
+impl<T, const N: usize> Drop for SmallVec<T, N> {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
+
+impl<T, const N: usize> Deref for SmallVec<T, N> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        match &self.data {
+            // SAFETY: the first `len` items are initialized, and
+            // `MaybeUninit<T>` has the same layout as `T`.
+            Data::Inline { items, len } => unsafe { slice::from_raw_parts(items.as_ptr().cast::<T>(), *len) },
+            Data::Heap(items) => items,
+        }
+    }
+}
+
+impl<T, const N: usize> DerefMut for SmallVec<T, N> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        match &mut self.data {
+            // SAFETY: as in `deref`.
+            Data::Inline { items, len } => unsafe { slice::from_raw_parts_mut(items.as_mut_ptr().cast::<T>(), *len) },
+            Data::Heap(items) => items,
+        }
+    }
+}
+
+impl<T, const N: usize> Default for SmallVec<T, N> {
+    fn default() -> SmallVec<T, N> {
+        SmallVec::new()
+    }
+}
+
+impl<T: Clone, const N: usize> Clone for SmallVec<T, N> {
+    fn clone(&self) -> SmallVec<T, N> {
+        self.iter().cloned().collect()
+    }
+}
+
+impl<T: fmt::Debug, const N: usize> fmt::Debug for SmallVec<T, N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl<T: PartialEq, const N: usize> PartialEq for SmallVec<T, N> {
+    fn eq(&self, other: &SmallVec<T, N>) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl<T: Eq, const N: usize> Eq for SmallVec<T, N> {}
+
+impl<T, const N: usize> Extend<T> for SmallVec<T, N> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, const N: usize> FromIterator<T> for SmallVec<T, N> {
+    fn from_iter<I: IntoIterator<Item = T>>(iter: I) -> SmallVec<T, N> {
+        let mut items = SmallVec::new();
+        items.extend(iter);
+        items
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a SmallVec<T, N> {
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.iter()
+    }
+}
+
+impl<'a, T, const N: usize> IntoIterator for &'a mut SmallVec<T, N> {
+    type Item = &'a mut T;
+    type IntoIter = slice::IterMut<'a, T>;
+
+    fn into_iter(self) -> slice::IterMut<'a, T> {
+        self.iter_mut()
+    }
+}
+
+pub struct IntoIter<T, const N: usize> {
+    data: IntoIterData<T, N>,
+}
+
+enum IntoIterData<T, const N: usize> {
+    // Invariant: `items[front..back]` are initialized and not yet yielded.
+    Inline { items: [MaybeUninit<T>; N], front: usize, back: usize },
+    Heap(vec::IntoIter<T>),
+}
+
+impl<T, const N: usize> IntoIterator for SmallVec<T, N> {
+    type Item = T;
+    type IntoIter = IntoIter<T, N>;
+
+    fn into_iter(mut self) -> IntoIter<T, N> {
+        // Leave an empty inline vector behind for `self` to drop.
+        let data = match std::mem::replace(&mut self.data, Data::Inline { items: [const { MaybeUninit::uninit() }; N], len: 0 }) {
+            Data::Inline { items, len } => IntoIterData::Inline { items, front: 0, back: len },
+            Data::Heap(items) => IntoIterData::Heap(items.into_iter()),
+        };
+        IntoIter { data }
+    }
+}
+
+impl<T, const N: usize> Iterator for IntoIter<T, N> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        match &mut self.data {
+            IntoIterData::Inline { items, front, back } => {
+                if front == back {
+                    return None;
+                }
+                *front += 1;
+                // SAFETY: the item was in `front..back`, so initialized and
+                // not yielded, and it is outside the range now.
+                Some(unsafe { items[*front - 1].assume_init_read() })
+            }
+            IntoIterData::Heap(items) => items.next(),
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = match &self.data {
+            IntoIterData::Inline { front, back, .. } => back - front,
+            IntoIterData::Heap(items) => items.len(),
+        };
+        (len, Some(len))
+    }
+}
+
+impl<T, const N: usize> DoubleEndedIterator for IntoIter<T, N> {
+    fn next_back(&mut self) -> Option<T> {
+        match &mut self.data {
+            IntoIterData::Inline { items, front, back } => {
+                if front == back {
+                    return None;
+                }
+                *back -= 1;
+                // SAFETY: as in `next`.
+                Some(unsafe { items[*back].assume_init_read() })
+            }
+            IntoIterData::Heap(items) => items.next_back(),
+        }
+    }
+}
+
+impl<T, const N: usize> ExactSizeIterator for IntoIter<T, N> {}
+
+impl<T, const N: usize> Drop for IntoIter<T, N> {
+    fn drop(&mut self) {
+        // Dropping the items one by one keeps the invariant if one panics.
+        if let IntoIterData::Inline { .. } = self.data {
+            for _ in self.by_ref() {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    use super::*;
+
+    // Counts its drops in a shared counter.
+    #[derive(Debug, Clone)]
+    struct Tracked(Rc<Cell<usize>>);
+
+    impl Drop for Tracked {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    fn tracked(count: usize) -> (Rc<Cell<usize>>, Vec<Tracked>) {
+        let drops = Rc::new(Cell::new(0));
+        let items = (0..count).map(|_| Tracked(Rc::clone(&drops))).collect();
+        (drops, items)
+    }
+
+    #[test]
+    fn should_stay_inline_until_full() {
+        let mut items: SmallVec<u8, 3> = SmallVec::new();
+        items.extend([1, 2, 3]);
+
+        assert!(!items.spilled());
+        assert_eq!(items.capacity(), 3);
+        assert_eq!(*items, [1, 2, 3]);
+
+        items.push(4);
+        assert!(items.spilled());
+        assert_eq!(*items, [1, 2, 3, 4]);
+        assert_eq!(items.pop(), Some(4));
+        assert!(items.spilled());
+    }
+
+    #[test]
+    fn should_work_as_a_slice() {
+        let mut items: SmallVec<i32, 4> = [3, 1, 2].into_iter().collect();
+
+        items.sort();
+        items[0] = 10;
+        for item in &mut items {
+            *item *= 2;
+        }
+
+        assert_eq!(items.iter().sum::<i32>(), 30);
+        assert_eq!(items.clone(), items);
+        assert_eq!(format!("{:?}", items), "[20, 4, 6]");
+        assert_eq!(items.into_iter().rev().collect::<Vec<_>>(), [6, 4, 20]);
+    }
+
+    #[test]
+    fn should_drop_each_item_once() {
+        for spill in [false, true] {
+            let (drops, values) = tracked(if spill { 5 } else { 3 });
+            let mut items: SmallVec<Tracked, 3> = values.into_iter().collect();
+            assert_eq!(items.spilled(), spill);
+
+            drop(items.pop());
+            assert_eq!(drops.get(), 1);
+            items.truncate(1);
+            assert_eq!(drops.get(), if spill { 4 } else { 2 });
+            drop(items);
+            assert_eq!(drops.get(), if spill { 5 } else { 3 });
+        }
+    }
+
+    #[test]
+    fn should_drop_what_into_iter_did_not_yield() {
+        let (drops, values) = tracked(4);
+        let items: SmallVec<Tracked, 4> = values.into_iter().collect();
+
+        let mut iter = items.into_iter();
+        assert_eq!(iter.len(), 4);
+        let first = iter.next().unwrap();
+        let last = iter.next_back().unwrap();
+        assert_eq!(drops.get(), 0);
+        drop(iter);
+        assert_eq!(drops.get(), 2);
+        drop((first, last));
+        assert_eq!(drops.get(), 4);
+    }
+
+    #[test]
+    fn should_move_items_once_when_spilling() {
+        let (drops, values) = tracked(3);
+        let mut items: SmallVec<Tracked, 2> = SmallVec::new();
+        items.extend(values);
+
+        assert!(items.spilled());
+        assert_eq!(drops.get(), 0);
+        assert_eq!(items.clone().into_vec().len(), 3);
+        assert_eq!(drops.get(), 3);
+        items.clear();
+        assert_eq!(drops.get(), 6);
+    }
+
+    #[test]
+    fn should_handle_zero_capacity_and_zero_sized_items() {
+        let mut empty: SmallVec<(), 0> = SmallVec::new();
+        assert_eq!(empty.pop(), None);
+        empty.push(());
+        assert!(empty.spilled());
+
+        let mut units: SmallVec<(), 2> = SmallVec::new();
+        units.extend([(), ()]);
+        assert_eq!(units.len(), 2);
+        assert!(!units.spilled());
+    }
+}
@@ -0,0 +1,52 @@
+//! Helpers shared by the unit tests of several modules.
+
+use std::env;
+use std::fs;
+use std::path::Path;
+
+use crate::binary_tree::BinaryTree;
+
+/// The tree most of the binary tree tests start from:
+///
+/// ```text
+///        10
+///      /    \
+///     5      60
+///    / \    /
+///   3   6  25
+///         /
+///        18
+/// ```
+pub(crate) fn sample() -> BinaryTree<u32> {
+    [10, 5, 6, 3, 60, 25, 18].into_iter().collect()
+}
+
+/// xorshift64, so the randomized tests are reproducible without a
+/// dependency.
+pub(crate) struct Rng(pub(crate) u64);
+
+impl Rng {
+    pub(crate) fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    pub(crate) fn below(&mut self, n: u64) -> u32 {
+        (self.next() % n) as u32
+    }
+}
+
+/// Compares `actual` with `testdata/<name>`. Run the tests with
+/// `UPDATE_GOLDEN=1` to write `actual` there first, after checking the
+/// change is intended.
+pub(crate) fn assert_snapshot(name: &str, actual: &str) {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(name);
+
+    if env::var_os("UPDATE_GOLDEN").is_some() {
+        fs::write(&path, actual).unwrap();
+    }
+    let expected = fs::read_to_string(&path).unwrap_or_else(|e| panic!("cannot read {}: {}", path.display(), e));
+    assert_eq!(actual, expected, "output differs from {}", path.display());
+}